@@ -0,0 +1,622 @@
+//! HTTP API of the Dead Man's Switch.
+//!
+//! Lets the owner check in without the TUI, e.g. from a cron job or a phone
+//! shortcut, with a `POST /api/check-in` request carrying an
+//! `Authorization: Bearer <api_token>` header, see [`spawn_check_ins`].
+//! A `POST /api/extend?by=3d` request postpones the deadline instead,
+//! see [`Timer::extend`](crate::timer::Timer::extend).
+//!
+//! Once the switch fired, the recipients stop the resends of the dead man's
+//! email with the unauthenticated `GET /acknowledge/<token>` link in it,
+//! see [`Timer::acknowledge`](crate::timer::Timer::acknowledge).
+//!
+//! Responses are JSON with a `status`, e.g. `{"status":"checked_in"}`.
+//! After [`MAX_FAILED_ATTEMPTS`] wrong tokens in a row, a client is locked
+//! out for the [`LOCKOUT`], so the token can't be brute-forced.
+//! The API only speaks plain HTTP, so it listens on localhost by default;
+//! put it behind a TLS reverse proxy to expose it.
+//!
+//! Every connection is handled on its own thread, up to
+//! [`MAX_CONNECTIONS`] at once, and has [`READ_TIMEOUT`] to send a request
+//! of lines up to [`MAX_LINE_LENGTH`] long, so slow or huge requests can't
+//! stall the API.
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config::{parse_duration, Config};
+use crate::timer::extension;
+
+/// The path of the check-in endpoint.
+const CHECK_IN_PATH: &str = "/api/check-in";
+
+/// The path of the extend endpoint.
+const EXTEND_PATH: &str = "/api/extend";
+
+/// The path of the acknowledgment link, before its token.
+pub const ACKNOWLEDGE_PATH: &str = "/acknowledge/";
+
+/// The page shown once the dead man's email is acknowledged.
+const ACKNOWLEDGED_PAGE: &str =
+    "<!DOCTYPE html><html><body><p>Thank you, the email won't be sent again.</p></body></html>";
+
+/// Maximum number of headers read from a request.
+const MAX_HEADERS: usize = 100;
+
+/// Maximum length in bytes of the request line and of each header.
+pub const MAX_LINE_LENGTH: u64 = 8 * 1024;
+
+/// Time a client has to send its whole request.
+pub const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Maximum number of connections handled at once, more are dropped.
+pub const MAX_CONNECTIONS: usize = 16;
+
+/// Wrong tokens in a row after which a client is locked out.
+pub const MAX_FAILED_ATTEMPTS: u32 = 5;
+
+/// How long a client is locked out for.
+pub const LOCKOUT: Duration = Duration::from_secs(15 * 60);
+
+/// What an API request asks the switch to do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiRequest {
+    /// Check in.
+    CheckIn,
+    /// Postpone the deadline by the duration.
+    Extend(Duration),
+    /// Acknowledge the dead man's email with the token of its link.
+    Acknowledge(String),
+}
+
+/// The failed attempts of a client.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Failures {
+    /// Wrong tokens in a row.
+    count: u32,
+    /// Until when the client is locked out, if it is.
+    locked_until: Option<Instant>,
+}
+
+/// Failed attempts per client, to lock out clients guessing the token.
+#[derive(Debug, Default)]
+struct Lockouts {
+    /// The clients with failed attempts.
+    clients: HashMap<IpAddr, Failures>,
+}
+
+impl Lockouts {
+    /// Check if the client is locked out, forgetting an expired lockout.
+    fn is_locked_out(&mut self, client: IpAddr, now: Instant) -> bool {
+        match self.clients.get(&client).and_then(|f| f.locked_until) {
+            Some(until) if until > now => true,
+            Some(_) => {
+                self.clients.remove(&client);
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Record a wrong token, locking the client out after too many.
+    fn fail(&mut self, client: IpAddr, now: Instant) {
+        // Forget expired lockouts, so the map doesn't grow forever
+        self.clients
+            .retain(|_, failures| !matches!(failures.locked_until, Some(until) if until <= now));
+
+        let failures = self.clients.entry(client).or_default();
+        failures.count += 1;
+        if failures.count >= MAX_FAILED_ATTEMPTS {
+            failures.locked_until = Some(now + LOCKOUT);
+        }
+    }
+
+    /// Record a right token, resetting the client's failed attempts.
+    fn succeed(&mut self, client: IpAddr) {
+        self.clients.remove(&client);
+    }
+}
+
+/// Spawn the thread serving the HTTP API, if an `api_token` or
+/// `dead_man_resends` are configured.
+///
+/// The returned [`Receiver`] gets a message for every request.
+/// Without an `api_token`, only the acknowledgment link is served.
+///
+/// ## Errors
+///
+/// - If the `api_listen` address cannot be bound.
+pub fn spawn_check_ins(config: &Config) -> io::Result<Option<Receiver<ApiRequest>>> {
+    if config.api_token.is_none() && config.dead_man_resends.is_empty() {
+        return Ok(None);
+    }
+    let listener = TcpListener::bind(&config.api_listen)?;
+    serve(listener, config.api_token.clone()).map(Some)
+}
+
+/// Serve the check-in, extend and acknowledgment endpoints on `listener`
+/// on a separate thread.
+fn serve(listener: TcpListener, token: Option<String>) -> io::Result<Receiver<ApiRequest>> {
+    let (sender, receiver) = mpsc::channel();
+    let lockouts = Arc::new(Mutex::new(Lockouts::default()));
+
+    serve_connections(listener, "api", move |stream| {
+        // A failed request doesn't stop the API
+        let _ = handle(stream, token.as_deref(), &sender, &lockouts);
+    })?;
+    Ok(receiver)
+}
+
+/// Accept connections on `listener` on a separate thread named `name`,
+/// handling each one on its own thread with a clone of `handler`.
+///
+/// Connections over the [`MAX_CONNECTIONS`] are dropped right away.
+///
+/// ## Errors
+///
+/// - If the thread cannot be spawned.
+pub(crate) fn serve_connections<F>(listener: TcpListener, name: &str, handler: F) -> io::Result<()>
+where
+    F: Fn(TcpStream) + Clone + Send + 'static,
+{
+    let connection_name = format!("{name}-connection");
+    let active = Arc::new(AtomicUsize::new(0));
+
+    thread::Builder::new()
+        .name(name.to_string())
+        .spawn(move || {
+            // A failed connection doesn't stop the listener
+            for stream in listener.incoming().flatten() {
+                if active.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+                    active.fetch_sub(1, Ordering::SeqCst);
+                    continue;
+                }
+                let connection = Connection(Arc::clone(&active));
+                let handler = handler.clone();
+                let _ = thread::Builder::new()
+                    .name(connection_name.clone())
+                    .spawn(move || {
+                        let _connection = connection;
+                        handler(stream);
+                    });
+            }
+        })?;
+    Ok(())
+}
+
+/// A connection being handled, counted in the active connections
+/// until it's dropped.
+struct Connection(Arc<AtomicUsize>);
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// A stream whose reads fail once its deadline has passed.
+struct Deadline<'a> {
+    /// The stream read from.
+    stream: &'a TcpStream,
+    /// When reading stops.
+    deadline: Instant,
+}
+
+impl Read for Deadline<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "the request took too long",
+            ));
+        }
+        self.stream.set_read_timeout(Some(remaining))?;
+        self.stream.read(buf)
+    }
+}
+
+/// Read a line of at most [`MAX_LINE_LENGTH`] bytes.
+fn read_line(reader: &mut impl BufRead) -> io::Result<String> {
+    let mut line = String::new();
+    reader.take(MAX_LINE_LENGTH).read_line(&mut line)?;
+    if !line.ends_with('\n') && line.len() as u64 >= MAX_LINE_LENGTH {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "the request line or a header is too long",
+        ));
+    }
+    Ok(line)
+}
+
+/// The parts of an HTTP request the API looks at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Request {
+    /// The method, e.g. `POST`.
+    pub(crate) method: String,
+    /// The path, e.g. `/api/check-in`.
+    pub(crate) path: String,
+    /// The value of the `Authorization` header, if any.
+    pub(crate) authorization: Option<String>,
+}
+
+/// Read the request line and headers of an HTTP request.
+///
+/// ## Errors
+///
+/// - If the request cannot be read within the [`READ_TIMEOUT`].
+/// - If a line is over the [`MAX_LINE_LENGTH`].
+pub(crate) fn read_request(stream: &TcpStream) -> io::Result<Request> {
+    let mut reader = BufReader::new(Deadline {
+        stream,
+        deadline: Instant::now() + READ_TIMEOUT,
+    });
+
+    let request_line = read_line(&mut reader)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut authorization = None;
+    for _ in 0..MAX_HEADERS {
+        let header = read_line(&mut reader)?;
+        if header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("authorization") {
+                authorization = Some(value.trim().to_string());
+            }
+        }
+    }
+
+    Ok(Request {
+        method,
+        path,
+        authorization,
+    })
+}
+
+/// Write an HTTP response and close the connection.
+///
+/// ## Errors
+///
+/// - If the response cannot be written.
+pub(crate) fn respond(
+    mut stream: TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &str,
+) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
+
+/// Handle a single request and respond to it.
+fn handle(
+    stream: TcpStream,
+    token: Option<&str>,
+    sender: &Sender<ApiRequest>,
+    lockouts: &Mutex<Lockouts>,
+) -> io::Result<()> {
+    let client = stream.peer_addr()?.ip();
+    let request = read_request(&stream)?;
+    let now = Instant::now();
+    let (path, query) = request
+        .path
+        .split_once('?')
+        .unwrap_or((request.path.as_str(), ""));
+
+    // The link in the dead man's email is clicked in a browser
+    if let Some(ack_token) = path.strip_prefix(ACKNOWLEDGE_PATH) {
+        return acknowledge(stream, &request.method, ack_token, sender);
+    }
+
+    let mut lockouts = match lockouts.lock() {
+        Ok(lockouts) => lockouts,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+
+    let (status, body) = if path != CHECK_IN_PATH && path != EXTEND_PATH {
+        ("404 Not Found", "not_found")
+    } else if request.method != "POST" {
+        ("405 Method Not Allowed", "method_not_allowed")
+    } else if lockouts.is_locked_out(client, now) {
+        ("429 Too Many Requests", "locked_out")
+    } else if !token.map_or(false, |token| {
+        authorized(request.authorization.as_deref(), token)
+    }) {
+        lockouts.fail(client, now);
+        ("401 Unauthorized", "unauthorized")
+    } else {
+        lockouts.succeed(client);
+        match parse_request(path, query) {
+            Some(api_request) => {
+                let done = match api_request {
+                    ApiRequest::CheckIn => "checked_in",
+                    _ => "extended",
+                };
+                match sender.send(api_request) {
+                    Ok(()) => ("200 OK", done),
+                    // Nobody is listening anymore
+                    Err(_) => ("503 Service Unavailable", "unavailable"),
+                }
+            }
+            None => ("400 Bad Request", "invalid_duration"),
+        }
+    };
+    drop(lockouts);
+    let body = format!("{{\"status\":\"{body}\"}}");
+    respond(stream, status, "application/json", &body)
+}
+
+/// Handle a click on the acknowledgment link.
+///
+/// The token is checked by the timer, so a wrong one gets the same page
+/// and can't be told apart.
+fn acknowledge(
+    stream: TcpStream,
+    method: &str,
+    token: &str,
+    sender: &Sender<ApiRequest>,
+) -> io::Result<()> {
+    if method != "GET" {
+        return respond(
+            stream,
+            "405 Method Not Allowed",
+            "text/plain",
+            "method_not_allowed",
+        );
+    }
+    match sender.send(ApiRequest::Acknowledge(token.to_string())) {
+        Ok(()) => respond(stream, "200 OK", "text/html", ACKNOWLEDGED_PAGE),
+        Err(_) => respond(
+            stream,
+            "503 Service Unavailable",
+            "text/plain",
+            "unavailable",
+        ),
+    }
+}
+
+/// The [`ApiRequest`] of a known `path`.
+///
+/// `None` if the `by` duration of an extension is missing, invalid, zero
+/// or over the [`MAX_EXTENSION`](crate::timer::MAX_EXTENSION).
+fn parse_request(path: &str, query: &str) -> Option<ApiRequest> {
+    if path == CHECK_IN_PATH {
+        return Some(ApiRequest::CheckIn);
+    }
+    query
+        .split('&')
+        .find_map(|parameter| parameter.strip_prefix("by="))
+        .and_then(parse_duration)
+        .and_then(extension)
+        .map(ApiRequest::Extend)
+}
+
+/// Check that the `Authorization` header carries the bearer `token`.
+fn authorized(authorization: Option<&str>, token: &str) -> bool {
+    match authorization.and_then(|value| value.strip_prefix("Bearer ")) {
+        Some(bearer) => constant_time_eq(bearer.trim().as_bytes(), token.as_bytes()),
+        None => false,
+    }
+}
+
+/// Compare in time independent of where the inputs differ,
+/// so the token can't be guessed byte by byte from response times.
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Serve the API with the token `secret` on a free port.
+    fn serve_test() -> (String, Receiver<ApiRequest>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}{CHECK_IN_PATH}", listener.local_addr().unwrap());
+        (url, serve(listener, Some("secret".to_string())).unwrap())
+    }
+
+    /// The status code of a response, error statuses included.
+    fn status(response: Result<ureq::Response, ureq::Error>) -> u16 {
+        match response {
+            Ok(response) => response.status(),
+            Err(ureq::Error::Status(status, _)) => status,
+            Err(error) => panic!("request failed: {error}"),
+        }
+    }
+
+    #[test]
+    fn test_check_in() {
+        let (url, check_ins) = serve_test();
+
+        let response = ureq::post(&url)
+            .set("Authorization", "Bearer secret")
+            .call()
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(
+            response.into_string().unwrap(),
+            r#"{"status":"checked_in"}"#
+        );
+        assert_eq!(check_ins.try_recv(), Ok(ApiRequest::CheckIn));
+    }
+
+    #[test]
+    fn test_extend() {
+        let (url, requests) = serve_test();
+        let url = url.replace(CHECK_IN_PATH, EXTEND_PATH);
+
+        let response = ureq::post(&format!("{url}?by=3d"))
+            .set("Authorization", "Bearer secret")
+            .call()
+            .unwrap();
+        assert_eq!(response.into_string().unwrap(), r#"{"status":"extended"}"#);
+        assert_eq!(
+            requests.try_recv(),
+            Ok(ApiRequest::Extend(Duration::from_secs(3 * 24 * 60 * 60)))
+        );
+
+        for query in [
+            "",
+            "?by=soon",
+            "?by=0",
+            "?by=18446744073709551615",
+            "?by=1000w",
+        ] {
+            let invalid =
+                ureq::post(&format!("{url}{query}")).set("Authorization", "Bearer secret");
+            assert_eq!(status(invalid.call()), 400);
+        }
+        assert!(requests.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_acknowledge() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = serve(listener, None).unwrap();
+
+        let response = ureq::get(&format!("{url}{ACKNOWLEDGE_PATH}abc123"))
+            .call()
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.content_type(), "text/html");
+        assert_eq!(
+            requests.try_recv(),
+            Ok(ApiRequest::Acknowledge("abc123".to_string()))
+        );
+
+        // Without an `api_token` nobody can check in
+        let check_in = ureq::post(&format!("{url}{CHECK_IN_PATH}"))
+            .set("Authorization", "Bearer ")
+            .call();
+        assert_eq!(status(check_in), 401);
+        let post = ureq::post(&format!("{url}{ACKNOWLEDGE_PATH}abc123")).call();
+        assert_eq!(status(post), 405);
+        assert!(requests.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_check_in_needs_the_token() {
+        let (url, check_ins) = serve_test();
+
+        assert_eq!(status(ureq::post(&url).call()), 401);
+        let wrong = ureq::post(&url).set("Authorization", "Bearer guess");
+        assert_eq!(status(wrong.call()), 401);
+        let basic = ureq::post(&url).set("Authorization", "Basic secret");
+        assert_eq!(status(basic.call()), 401);
+        let get = ureq::get(&url).set("Authorization", "Bearer secret");
+        assert_eq!(status(get.call()), 405);
+        assert!(check_ins.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_lockout() {
+        let (url, check_ins) = serve_test();
+
+        for _ in 0..MAX_FAILED_ATTEMPTS {
+            let wrong = ureq::post(&url).set("Authorization", "Bearer guess");
+            assert_eq!(status(wrong.call()), 401);
+        }
+        // Even the right token is refused while locked out
+        let right = ureq::post(&url).set("Authorization", "Bearer secret");
+        assert_eq!(status(right.call()), 429);
+        assert!(check_ins.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_slow_clients_dont_block() {
+        let (url, check_ins) = serve_test();
+        let address = url
+            .trim_start_matches("http://")
+            .trim_end_matches(CHECK_IN_PATH);
+
+        // A client that never sends its request
+        let _slow = TcpStream::connect(address).unwrap();
+        let started = Instant::now();
+        let response = ureq::post(&url)
+            .set("Authorization", "Bearer secret")
+            .call()
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        assert!(started.elapsed() < READ_TIMEOUT);
+        assert_eq!(check_ins.try_recv(), Ok(ApiRequest::CheckIn));
+    }
+
+    #[test]
+    fn test_read_request_limits() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+
+        // An endless request line is cut off instead of read whole
+        let long_path = "a".repeat(MAX_LINE_LENGTH as usize);
+        client
+            .write_all(format!("GET /{long_path} HTTP/1.1\r\n").as_bytes())
+            .unwrap();
+        let error = read_request(&server).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        // A short line read fine
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        client
+            .write_all(b"POST /api/check-in HTTP/1.1\r\nAuthorization: Bearer x\r\n\r\n")
+            .unwrap();
+        let request = read_request(&server).unwrap();
+        assert_eq!(request.path, CHECK_IN_PATH);
+        assert_eq!(request.authorization.as_deref(), Some("Bearer x"));
+    }
+
+    #[test]
+    fn test_lockouts() {
+        let client = IpAddr::from([192, 0, 2, 1]);
+        let other = IpAddr::from([192, 0, 2, 2]);
+        let now = Instant::now();
+        let mut lockouts = Lockouts::default();
+
+        // A right token resets the count
+        for _ in 1..MAX_FAILED_ATTEMPTS {
+            lockouts.fail(client, now);
+        }
+        lockouts.succeed(client);
+        lockouts.fail(client, now);
+        assert!(!lockouts.is_locked_out(client, now));
+
+        for _ in 1..MAX_FAILED_ATTEMPTS {
+            lockouts.fail(client, now);
+        }
+        assert!(lockouts.is_locked_out(client, now));
+        assert!(!lockouts.is_locked_out(other, now));
+
+        // The lockout expires
+        assert!(!lockouts.is_locked_out(client, now + LOCKOUT));
+        assert!(lockouts.clients.is_empty());
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secrets"));
+    }
+
+    #[test]
+    fn test_spawn_check_ins_without_token() {
+        assert!(spawn_check_ins(&Config::default()).unwrap().is_none());
+    }
+}
@@ -1,14 +1,23 @@
 //! Configuration module for the Dead Man's Switch
 //! Contains functions and structs to handle the configuration.
-use std::fs::{self, File};
-use std::io::Write;
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
 
+use chrono::Local;
 use directories_next::BaseDirs;
-use serde::{Deserialize, Serialize};
+use lettre::{message::Mailbox, Address};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use tempfile::NamedTempFile;
 use thiserror::Error;
 use toml::{de::Error as DerTomlError, ser::Error as SerTomlError};
 
+use crate::i18n::Language;
+use crate::signal::SignalConfig;
+use crate::timer::ClockSource;
+
 /// Configuration struct used for the application
 ///
 /// ## Default
@@ -16,37 +25,533 @@ use toml::{de::Error as DerTomlError, ser::Error as SerTomlError};
 /// If the configuration file does not exist, it will be created with
 /// the default values.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Config {
     /// The username for the email account.
     pub username: String,
     /// The password for the email account.
+    ///
+    /// Secret: keep it out of logs and version control,
+    /// or use the `password_command` instead.
     pub password: String,
+    /// Command that prints the password, e.g. from the OS keyring.
+    ///
+    /// If set, it runs through the shell before every send and its output
+    /// is used instead of the `password`, so the password doesn't need to be
+    /// stored in the config.
+    #[serde(default)]
+    pub password_command: Option<String>,
+    /// How the emails are sent.
+    #[serde(default)]
+    pub transport: EmailTransport,
+    /// The directory the `file` transport writes the emails to.
+    #[serde(default)]
+    pub file_transport_dir: Option<PathBuf>,
+    /// Log the emails to `dry_run.log` next to the config instead of
+    /// sending them, see [`dry_run_log_path`].
+    ///
+    /// The other channels, the `on_deadman_command` and the config wipe
+    /// are likewise only logged there.
+    #[serde(default)]
+    pub dry_run: bool,
     /// The SMTP server to use
     pub smtp_server: String,
     /// The port to use for the SMTP server.
     pub smtp_port: u16,
+    /// The SMTP authentication mechanism to use.
+    ///
+    /// If not set, the mechanism is negotiated with the server.
+    #[serde(default)]
+    pub smtp_auth_mechanism: Option<SmtpAuthMechanism>,
+    /// Command that prints an OAuth2 access token, e.g. from a token helper.
+    ///
+    /// With the `xoauth2` mechanism, it runs through the shell before every
+    /// send and its output is used instead of the `password`, so an expired
+    /// token is refreshed by the helper.
+    #[serde(default)]
+    pub oauth2_token_command: Option<String>,
+    /// How the connection to the SMTP server is secured.
+    #[serde(default)]
+    pub tls_mode: TlsMode,
+    /// How many times to retry sending an email after a transient failure,
+    /// with exponential backoff.
+    #[serde(default = "default_smtp_max_retries")]
+    pub smtp_max_retries: u32,
     /// The message to send in the email if you fail to check in
     /// after the `timer_warning` with the additional `timer_dead_man`
     /// seconds have passed.
     pub message: String,
     /// The warning message if you fail to check in `timer_warning` seconds.
     pub message_warning: String,
+    /// HTML version of the `message`, sent as an alternative to the
+    /// plain text.
+    #[serde(default)]
+    pub message_html: Option<String>,
+    /// HTML version of the `message_warning`, sent as an alternative to the
+    /// plain text.
+    #[serde(default)]
+    pub message_warning_html: Option<String>,
     /// The subject of the email if you fail to check in
     /// after the `timer_warning` with the additional `timer_dead_man`
     /// seconds have passed.
     pub subject: String,
     /// The subject of the email if you fail to check in `timer_warning` seconds.
     pub subject_warning: String,
+    /// The subject of the test email.
+    #[serde(default = "default_subject_test")]
+    pub subject_test: String,
+    /// The message of the test email.
+    #[serde(default = "default_message_test")]
+    pub message_test: String,
+    /// The subject of the final grace email,
+    /// the last chance to check in before the dead man's email is sent.
+    #[serde(default = "default_subject_final_grace")]
+    pub subject_final_grace: String,
+    /// The message of the final grace email.
+    #[serde(default = "default_message_final_grace")]
+    pub message_final_grace: String,
+    /// The subject of the check-in confirmation email.
+    #[serde(default = "default_subject_check_in")]
+    pub subject_check_in: String,
+    /// The message of the check-in confirmation email.
+    #[serde(default = "default_message_check_in")]
+    pub message_check_in: String,
+    /// The subject of the reminder email, see `reminder_before_secs`.
+    #[serde(default = "default_subject_reminder")]
+    pub subject_reminder: String,
+    /// The message of the reminder email.
+    #[serde(default = "default_message_reminder")]
+    pub message_reminder: String,
     /// The email address to send the email to.
+    ///
+    /// Multiple addresses are separated by commas.
+    /// `@group:<name>` expands to the addresses of the group `<name>`
+    /// in the `recipients_file`.
     pub to: String,
     /// The email address to send the email from.
     pub from: String,
-    /// Attachment to send with the email.
-    pub attachment: Option<PathBuf>,
+    /// The email addresses to copy the dead man's email to.
+    ///
+    /// Comma-separated like `to`, including `@group:<name>`.
+    #[serde(default)]
+    pub cc: Option<String>,
+    /// The email addresses to blind copy the dead man's email to.
+    ///
+    /// Comma-separated like `to`, including `@group:<name>`.
+    #[serde(default)]
+    pub bcc: Option<String>,
+    /// The address replies to the dead man's email go to,
+    /// instead of `from`.
+    #[serde(default)]
+    pub reply_to: Option<String>,
+    /// Whether to send a copy of the dead man's email to `from` as `Bcc`,
+    /// as a record of exactly what was sent.
+    #[serde(default)]
+    pub copy_self_on_deadman: bool,
+    /// Whether to send the warning email to the recipients in `to` too,
+    /// so they're ready to act if the dead man's email follows.
+    #[serde(default)]
+    pub warning_notify_recipients: bool,
+    /// Whether to send the check-in confirmation email to `from` on every
+    /// check-in through the API, as a record of check-ins made on your
+    /// behalf.
+    #[serde(default)]
+    pub ack_email_on_checkin: bool,
+    /// Attachments to send with the dead man's email.
+    ///
+    /// Accepts a single path or a list of paths,
+    /// also under the old `attachment` name.
+    #[serde(default, alias = "attachment", deserialize_with = "one_or_many")]
+    pub attachments: Vec<PathBuf>,
+    /// Maximum size in bytes of each attachment.
+    ///
+    /// Attachments grow by a third when encoded, so keep it below the
+    /// SMTP server's message size limit.
+    #[serde(default = "default_max_attachment_bytes")]
+    pub max_attachment_bytes: u64,
+    /// Send several attachments as a single `attachments.zip`.
+    #[serde(default)]
+    pub attachment_as_zip: bool,
+    /// Command whose output is attached to the dead man's email.
+    ///
+    /// Runs through the shell when the email is sent, e.g. to attach a
+    /// fresh export rather than a stale file.
+    /// The email isn't sent if the command fails.
+    #[serde(default)]
+    pub attachment_command: Option<String>,
+    /// Filename of the `attachment_command` output.
+    #[serde(default = "default_attachment_command_filename")]
+    pub attachment_command_filename: String,
+    /// MIME type of the `attachment_command` output.
+    ///
+    /// Guessed from the `attachment_command_filename` if not set.
+    #[serde(default)]
+    pub attachment_command_content_type: Option<String>,
+    /// Address book mapping group names to lists of addresses.
+    ///
+    /// A TOML file such as `family = ["mom@example.com", "dad@example.com"]`.
+    #[serde(default)]
+    pub recipients_file: Option<PathBuf>,
+    /// OpenPGP public key to encrypt the warning and the dead man's emails to.
+    ///
+    /// The emails are sent as PGP/MIME, encrypted with `gpg`,
+    /// and fail rather than go out in plain text.
+    #[serde(default)]
+    pub pgp_public_key_path: Option<PathBuf>,
     /// Timer in seconds for the warning email.
+    ///
+    /// Also accepts a human-readable duration, e.g. `"2w"`.
+    #[serde(deserialize_with = "duration", serialize_with = "human_duration")]
+    #[cfg_attr(feature = "schema", schemars(with = "HumanDuration"))]
     pub timer_warning: u64,
     /// Timer in seconds for the dead man's email.
+    ///
+    /// Also accepts a human-readable duration, e.g. `"1w"`.
+    #[serde(deserialize_with = "duration", serialize_with = "human_duration")]
+    #[cfg_attr(feature = "schema", schemars(with = "HumanDuration"))]
     pub timer_dead_man: u64,
+    /// Seconds before the warning timer expires to send a one-time
+    /// reminder email to `from`, if any.
+    #[serde(default)]
+    pub reminder_before_secs: Option<u64>,
+    /// Intervals at which to resend the dead man's email after the switch
+    /// fired, until a recipient acknowledges it with the link in the email.
+    ///
+    /// Each is the time after the previous email, e.g. `["1d", "2d", "4d"]`.
+    /// Empty by default, for a single email.
+    /// Needs the `public_url` of the acknowledgment link.
+    #[serde(
+        default,
+        deserialize_with = "durations",
+        serialize_with = "human_durations"
+    )]
+    #[cfg_attr(feature = "schema", schemars(with = "Vec<HumanDuration>"))]
+    pub dead_man_resends: Vec<u64>,
+    /// The clock used to measure the timers.
+    ///
+    /// Defaults to [`ClockSource::WallClock`] so that time spent while
+    /// the system is asleep still counts towards the deadline.
+    #[serde(default)]
+    pub clock: ClockSource,
+    /// URL to ping periodically to prove the switch is alive,
+    /// e.g. a [healthchecks.io](https://healthchecks.io) check.
+    #[serde(default)]
+    pub heartbeat_url: Option<String>,
+    /// Interval in seconds between heartbeat pings, must be positive.
+    #[serde(default = "default_heartbeat_interval_seconds")]
+    pub heartbeat_interval_seconds: u64,
+    /// URL to POST a JSON payload to when the switch fires,
+    /// see [`crate::webhook`].
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    /// Token of the Telegram bot that sends the warning and dead man's
+    /// messages, see [`crate::telegram`].
+    #[serde(default)]
+    pub telegram_bot_token: Option<String>,
+    /// The Telegram chat that receives the messages.
+    #[serde(default)]
+    pub telegram_chat_id: Option<String>,
+    /// The language of the user interface.
+    #[serde(default)]
+    pub language: Language,
+    /// Interval in milliseconds between checks for key presses and
+    /// timer changes in the TUI, must be positive.
+    ///
+    /// The TUI only redraws when the displayed time changes or on input,
+    /// so larger values mostly trade responsiveness for CPU usage.
+    #[serde(default = "default_tui_refresh_ms")]
+    pub tui_refresh_ms: u64,
+    /// Remaining percentage of the timer at or below which the TUI turns red.
+    #[serde(default = "default_warning_threshold_percent")]
+    pub warning_threshold_percent: u16,
+    /// Remaining percentage of the timer at or below which the TUI turns
+    /// yellow, before it turns red at `warning_threshold_percent`.
+    ///
+    /// Set it to `warning_threshold_percent` to go from green to red directly.
+    #[serde(default = "default_caution_threshold_percent")]
+    pub caution_threshold_percent: u16,
+    /// Whether to ring the terminal bell in the TUI once the warning is sent.
+    ///
+    /// Built with the `desktop-notifications` feature,
+    /// it shows a desktop notification too.
+    #[serde(default)]
+    pub alert_on_warning: bool,
+    /// Whether the switch is enabled.
+    ///
+    /// A disabled switch keeps its configuration but never fires.
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Overwrite and remove the config once the dead man's email is sent.
+    ///
+    /// Only after a successful send, so a thief can't reuse the
+    /// credentials, logged to `hook.log`.
+    #[serde(default)]
+    pub wipe_config_on_fire: bool,
+    /// Command to run after the dead man's email is sent.
+    ///
+    /// Only runs if `allow_command_hooks` is set, see [`crate::hook`]
+    /// for the security implications.
+    #[serde(default)]
+    pub on_deadman_command: Option<String>,
+    /// Allow running `on_deadman_command`.
+    #[serde(default)]
+    pub allow_command_hooks: bool,
+    /// Timeout in seconds after which `on_deadman_command`
+    /// and `attachment_command` are killed.
+    #[serde(default = "default_command_timeout_seconds")]
+    pub command_timeout_seconds: u64,
+    /// Token to check in with the HTTP API, see [`crate::api`].
+    ///
+    /// The API only runs if set.
+    /// Secret: keep it out of logs and version control.
+    #[serde(default)]
+    pub api_token: Option<String>,
+    /// Address the HTTP API listens on.
+    #[serde(default = "default_api_listen")]
+    pub api_listen: String,
+    /// URL the recipients reach the HTTP API at, e.g. through a reverse
+    /// proxy, for the acknowledgment link of the `dead_man_resends`.
+    #[serde(default)]
+    pub public_url: Option<String>,
+    /// The link to acknowledge the dead man's email with, while it is
+    /// resent, see [`crate::timer::Timer::acknowledge_url`].
+    ///
+    /// Set when sending, not part of the config file.
+    #[serde(skip)]
+    #[cfg_attr(feature = "schema", schemars(skip))]
+    pub acknowledge_url: Option<String>,
+    /// Address to serve Prometheus metrics on, see [`crate::metrics`].
+    ///
+    /// The metrics are unauthenticated, so use an internal address.
+    #[serde(default)]
+    pub metrics_listen: Option<String>,
+    /// Escalation stages between the warning and the dead man's email.
+    ///
+    /// Each sends its own email once its timer expires, before the
+    /// `timer_dead_man` countdown starts.
+    /// Empty by default, for the two stages of warning and dead man.
+    #[serde(default)]
+    pub escalations: Vec<Escalation>,
+    /// Separate dead man's emails, each with its own recipients,
+    /// message and attachments.
+    ///
+    /// Sent along with the dead man's email.
+    /// Skipped when empty, since an empty array value can't follow the
+    /// `escalations` tables.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub personal_emails: Vec<PersonalEmail>,
+    /// Extra headers added to every email, e.g. `Auto-Submitted`,
+    /// by name.
+    ///
+    /// Must come after the values and the `escalations`, since TOML tables
+    /// come after values, and no `escalations` is an empty array value.
+    #[serde(default)]
+    pub custom_headers: Option<BTreeMap<String, String>>,
+    /// Images embedded in the HTML messages, by the Content-ID they are
+    /// referenced by, e.g. `<img src="cid:logo">` for `logo`.
+    ///
+    /// Only the images an HTML message references are added to its email.
+    #[serde(default)]
+    pub inline_images: Option<BTreeMap<String, PathBuf>>,
+    /// The keys of the TUI actions.
+    #[serde(default)]
+    pub keybindings: Keybindings,
+    /// Send the messages over Signal too, and check in from it.
+    ///
+    /// Must be the last field, since TOML tables come after values.
+    #[serde(default)]
+    pub signal: Option<SignalConfig>,
+}
+
+/// Deserialize either a single path or a list of paths.
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<PathBuf>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(PathBuf),
+        Many(Vec<PathBuf>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(path) => vec![path],
+        OneOrMany::Many(paths) => paths,
+    })
+}
+
+/// Units of human-readable durations and their length in seconds,
+/// largest first.
+const DURATION_UNITS: [(char, u64); 5] = [
+    ('w', 60 * 60 * 24 * 7),
+    ('d', 60 * 60 * 24),
+    ('h', 60 * 60),
+    ('m', 60),
+    ('s', 1),
+];
+
+/// A duration in seconds, or human-readable such as `"14d"`, `"2w"` or
+/// `"36h"`.
+#[derive(Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(untagged)]
+enum HumanDuration {
+    Seconds(u64),
+    Human(String),
+}
+
+/// Parse a human-readable duration such as `"14d"` into seconds.
+///
+/// A number without a unit is in seconds.
+pub(crate) fn parse_duration(duration: &str) -> Option<u64> {
+    let duration = duration.trim();
+    let (number, unit) = match duration.char_indices().last()? {
+        (index, unit) if unit.is_ascii_alphabetic() => (&duration[..index], unit),
+        _ => (duration, 's'),
+    };
+    let (_, seconds) = DURATION_UNITS.iter().find(|(name, _)| *name == unit)?;
+    number.trim().parse::<u64>().ok()?.checked_mul(*seconds)
+}
+
+impl HumanDuration {
+    /// The duration in seconds.
+    fn seconds<E: de::Error>(self) -> Result<u64, E> {
+        match self {
+            HumanDuration::Seconds(seconds) => Ok(seconds),
+            HumanDuration::Human(duration) => parse_duration(&duration).ok_or_else(|| {
+                E::custom(format!(
+                    "invalid duration {duration:?}, expected e.g. \"14d\", \"2w\", \"36h\" \
+                     or a number of seconds"
+                ))
+            }),
+        }
+    }
+}
+
+/// Deserialize a duration in seconds or a human-readable one.
+fn duration<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    HumanDuration::deserialize(deserializer)?.seconds()
+}
+
+/// Deserialize a list of durations, see [`duration`].
+fn durations<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u64>, D::Error> {
+    Vec::<HumanDuration>::deserialize(deserializer)?
+        .into_iter()
+        .map(HumanDuration::seconds)
+        .collect()
+}
+
+/// Format a duration in seconds in the largest whole unit, e.g. `"2w"`.
+fn format_human_duration(seconds: u64) -> String {
+    let (unit, length) = DURATION_UNITS
+        .iter()
+        .find(|(_, length)| seconds >= *length && seconds % length == 0)
+        .copied()
+        .unwrap_or(('s', 1));
+    format!("{}{unit}", seconds / length)
+}
+
+/// Serialize a duration in seconds in the largest whole unit, e.g. `"2w"`.
+fn human_duration<S: Serializer>(seconds: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format_human_duration(*seconds))
+}
+
+/// Serialize a list of durations, see [`human_duration`].
+fn human_durations<S: Serializer>(seconds: &[u64], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(
+        seconds
+            .iter()
+            .map(|seconds| format_human_duration(*seconds)),
+    )
+}
+
+/// Default number of retries after a transient SMTP failure.
+fn default_smtp_max_retries() -> u32 {
+    3
+}
+
+/// Default maximum size of an attachment, 25 MiB.
+fn default_max_attachment_bytes() -> u64 {
+    25 * 1024 * 1024
+}
+
+/// Default filename of the `attachment_command` output.
+fn default_attachment_command_filename() -> String {
+    "attachment".to_string()
+}
+
+/// Default subject of the test email.
+fn default_subject_test() -> String {
+    "[Dead Man's Switch] Test email".to_string()
+}
+
+/// Default message of the test email.
+fn default_message_test() -> String {
+    "This is a test email from your Dead Man's Switch. If you can read this, your email settings work.".to_string()
+}
+
+/// Default subject of the final grace email.
+fn default_subject_final_grace() -> String {
+    "[URGENT] Last chance to check in!".to_string()
+}
+
+/// Default message of the final grace email.
+fn default_message_final_grace() -> String {
+    "Your Dead Man's Switch is about to be triggered. Check in now to stop it.".to_string()
+}
+
+/// Default subject of the check-in confirmation email.
+fn default_subject_check_in() -> String {
+    "[Dead Man's Switch] Check-in registered".to_string()
+}
+
+/// Default message of the check-in confirmation email.
+fn default_message_check_in() -> String {
+    "Your check-in was registered and the Dead Man's Switch was reset.".to_string()
+}
+
+/// Default subject of the reminder email.
+fn default_subject_reminder() -> String {
+    "[Dead Man's Switch] Time to check in".to_string()
+}
+
+/// Default message of the reminder email.
+fn default_message_reminder() -> String {
+    "Friendly reminder: check in with your Dead Man's Switch before the warning is sent."
+        .to_string()
+}
+
+/// Default interval in seconds between heartbeat pings.
+fn default_heartbeat_interval_seconds() -> u64 {
+    60 * 5 // 5 minutes
+}
+
+/// Default interval in milliseconds between TUI refreshes.
+fn default_tui_refresh_ms() -> u64 {
+    250
+}
+
+/// Default remaining percentage at which the TUI turns red.
+fn default_warning_threshold_percent() -> u16 {
+    30
+}
+
+/// Default remaining percentage at which the TUI turns yellow.
+fn default_caution_threshold_percent() -> u16 {
+    60
+}
+
+/// Default timeout in seconds of the command hooks.
+fn default_command_timeout_seconds() -> u64 {
+    60
+}
+
+/// Default address of the HTTP API, only reachable from this machine.
+fn default_api_listen() -> String {
+    "127.0.0.1:8787".to_string()
+}
+
+/// Switches are enabled by default.
+fn default_enabled() -> bool {
+    true
 }
 
 impl Default for Config {
@@ -54,21 +559,335 @@ impl Default for Config {
         Self {
             username: "me@example.com".to_string(),
             password: "".to_string(),
+            transport: EmailTransport::default(),
+            file_transport_dir: None,
+            dry_run: false,
             smtp_server: "smtp.example.com".to_string(),
             smtp_port: 587,
+            smtp_auth_mechanism: None,
+            password_command: None,
+            oauth2_token_command: None,
+            tls_mode: TlsMode::default(),
+            smtp_max_retries: default_smtp_max_retries(),
             message: "I'm probably dead, go to Central Park NY under bench #137 you'll find an age-encrypted drive. Password is our favorite music in Pascal case.".to_string(),
             message_warning: "Hey, you haven't checked in for a while. Are you okay?".to_string(),
+            message_html: None,
+            message_warning_html: None,
             subject: "[URGENT] Something Happened to Me!".to_string(),
             subject_warning: "[URGENT] You need to check in!".to_string(),
+            subject_test: default_subject_test(),
+            message_test: default_message_test(),
+            subject_final_grace: default_subject_final_grace(),
+            message_final_grace: default_message_final_grace(),
+            subject_check_in: default_subject_check_in(),
+            message_check_in: default_message_check_in(),
+            subject_reminder: default_subject_reminder(),
+            message_reminder: default_message_reminder(),
             to: "someone@example.com".to_string(),
             from: "me@example.com".to_string(),
-            attachment: None,
+            cc: None,
+            bcc: None,
+            reply_to: None,
+            copy_self_on_deadman: false,
+            warning_notify_recipients: false,
+            ack_email_on_checkin: false,
+            attachments: vec![],
+            max_attachment_bytes: default_max_attachment_bytes(),
+            attachment_as_zip: false,
+            attachment_command: None,
+            attachment_command_filename: default_attachment_command_filename(),
+            attachment_command_content_type: None,
+            recipients_file: None,
+            pgp_public_key_path: None,
             timer_warning: 60 * 60 * 24 * 14, // 2 weeks
             timer_dead_man: 60 * 60 * 24 * 7, // 1 week
+            reminder_before_secs: None,
+            dead_man_resends: vec![],
+            clock: ClockSource::default(),
+            heartbeat_url: None,
+            heartbeat_interval_seconds: default_heartbeat_interval_seconds(),
+            webhook_url: None,
+            telegram_bot_token: None,
+            telegram_chat_id: None,
+            language: Language::default(),
+            tui_refresh_ms: default_tui_refresh_ms(),
+            warning_threshold_percent: default_warning_threshold_percent(),
+            caution_threshold_percent: default_caution_threshold_percent(),
+            alert_on_warning: false,
+            enabled: default_enabled(),
+            wipe_config_on_fire: false,
+            on_deadman_command: None,
+            allow_command_hooks: false,
+            command_timeout_seconds: default_command_timeout_seconds(),
+            api_token: None,
+            api_listen: default_api_listen(),
+            public_url: None,
+            metrics_listen: None,
+            escalations: vec![],
+            personal_emails: vec![],
+            custom_headers: None,
+            inline_images: None,
+            keybindings: Keybindings::default(),
+            signal: None,
+            acknowledge_url: None,
+        }
+    }
+}
+
+impl Config {
+    /// Check the configuration for problems that would stop the switch
+    /// from sending its emails, so they show up right away.
+    ///
+    /// ## Errors
+    ///
+    /// - [`ConfigError::Invalid`] describing the first problem found:
+    ///   an empty `smtp_server`, a `smtp_port` of 0,
+    ///   the `file` transport without a `file_transport_dir`,
+    ///   a `from`, `to` or `reply_to` that doesn't parse,
+    ///   an invalid custom header, or a timer of 0.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |problem: String| Err(ConfigError::Invalid(problem));
+
+        match self.transport {
+            EmailTransport::Smtp => {
+                if self.smtp_server.trim().is_empty() {
+                    return invalid("`smtp_server` is empty".to_string());
+                }
+                if self.smtp_port == 0 {
+                    return invalid("`smtp_port` is 0".to_string());
+                }
+            }
+            EmailTransport::Sendmail => {}
+            EmailTransport::File => {
+                if self.file_transport_dir.is_none() {
+                    return invalid(
+                        "`transport` is `file` but `file_transport_dir` is not set".to_string(),
+                    );
+                }
+            }
+        }
+        if let Err(error) = self.from.parse::<Address>() {
+            return invalid(format!(
+                "`from` {:?} is not an email address: {error}",
+                self.from
+            ));
+        }
+        if let Err(error) = self.recipients(&self.to) {
+            return invalid(format!("`to` {:?}: {error}", self.to));
+        }
+        for personal in &self.personal_emails {
+            if let Err(error) = self.recipients(&personal.to) {
+                return invalid(format!("personal email `to` {:?}: {error}", personal.to));
+            }
+        }
+        if let Some(reply_to) = &self.reply_to {
+            if let Err(error) = reply_to.parse::<Mailbox>() {
+                return invalid(format!(
+                    "`reply_to` {reply_to:?} is not an email address: {error}"
+                ));
+            }
+        }
+        if let Err(error) = self.custom_headers() {
+            return invalid(error.to_string());
+        }
+        for cid in self.inline_images.iter().flatten().map(|(cid, _)| cid) {
+            let valid = |char: char| char.is_ascii_graphic() && char != '<' && char != '>';
+            if cid.is_empty() || !cid.chars().all(valid) {
+                return invalid(format!(
+                    "the inline image Content-ID {cid:?} must be printable ASCII without `<` or `>`"
+                ));
+            }
+        }
+        if self.timer_warning == 0 {
+            return invalid("`timer_warning` must be positive".to_string());
+        }
+        if self.timer_dead_man == 0 {
+            return invalid("`timer_dead_man` must be positive".to_string());
+        }
+        if self.dead_man_resends.contains(&0) {
+            return invalid("`dead_man_resends` must be positive".to_string());
         }
+        if self.heartbeat_interval_seconds == 0 {
+            return invalid("`heartbeat_interval_seconds` must be positive".to_string());
+        }
+        if self.tui_refresh_ms == 0 {
+            return invalid("`tui_refresh_ms` must be positive".to_string());
+        }
+        if !self.dead_man_resends.is_empty() && self.public_url.is_none() {
+            return invalid(
+                "`dead_man_resends` needs the `public_url` of the acknowledgment link".to_string(),
+            );
+        }
+        if self.caution_threshold_percent > 100 {
+            return invalid("`caution_threshold_percent` is over 100".to_string());
+        }
+        if self.warning_threshold_percent > self.caution_threshold_percent {
+            return invalid(
+                "`warning_threshold_percent` is over `caution_threshold_percent`".to_string(),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Non-fatal problems with the configuration.
+    ///
+    /// These don't prevent the switch from running, but likely prevent
+    /// the emails from arriving, e.g. sending `from` a domain that the SMTP
+    /// server is not authorized for by SPF/DMARC.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = vec![];
+
+        if let Some(from_domain) = domain(&self.from) {
+            let smtp_server = self.smtp_server.to_lowercase();
+            if smtp_server != from_domain && !smtp_server.ends_with(&format!(".{from_domain}")) {
+                warnings.push(format!(
+                    "The `from` domain {from_domain} doesn't match the SMTP server {}: \
+                     the emails may be rejected or marked as spam by SPF/DMARC checks.",
+                    self.smtp_server
+                ));
+            }
+            if let Some(username_domain) = domain(&self.username) {
+                if username_domain != from_domain {
+                    warnings.push(format!(
+                        "The `from` domain {from_domain} doesn't match the `username` domain \
+                         {username_domain}: the emails may be rejected or marked as spam by \
+                         SPF/DMARC checks."
+                    ));
+                }
+            }
+        }
+
+        if self.tls_mode == TlsMode::None {
+            warnings.push(
+                "`tls_mode` is `none`: the password and emails are sent in plain text.".to_string(),
+            );
+        }
+
+        if self.oauth2_token_command.is_some()
+            && self.smtp_auth_mechanism != Some(SmtpAuthMechanism::Xoauth2)
+        {
+            warnings.push(
+                "`oauth2_token_command` is set but only used with the `xoauth2` \
+                 `smtp_auth_mechanism`."
+                    .to_string(),
+            );
+        }
+
+        if self.telegram_bot_token.is_some() != self.telegram_chat_id.is_some() {
+            warnings.push(
+                "Telegram needs both `telegram_bot_token` and `telegram_chat_id`: \
+                 no Telegram messages will be sent."
+                    .to_string(),
+            );
+        }
+
+        let personal_attachments = self
+            .personal_emails
+            .iter()
+            .flat_map(|personal| &personal.attachments);
+        for attachment in self.attachments.iter().chain(personal_attachments) {
+            if let Ok(metadata) = fs::metadata(attachment) {
+                if metadata.len() > self.max_attachment_bytes {
+                    warnings.push(format!(
+                        "The attachment {} is larger than `max_attachment_bytes`: \
+                         the dead man's email will fail.",
+                        attachment.display()
+                    ));
+                }
+            }
+        }
+
+        if let Some(key) = &self.pgp_public_key_path {
+            if !key.is_file() {
+                warnings.push(format!(
+                    "The PGP public key {} is not a file: \
+                     the warning and the dead man's emails will fail.",
+                    key.display()
+                ));
+            }
+        }
+
+        if self.dry_run {
+            warnings.push(
+                "`dry_run` is enabled: the emails and other actions are written to dry_run.log \
+                 instead of being carried out."
+                    .to_string(),
+            );
+        }
+
+        if self.on_deadman_command.is_some() && !self.allow_command_hooks {
+            warnings.push(
+                "`on_deadman_command` is set but won't run unless `allow_command_hooks` is enabled."
+                    .to_string(),
+            );
+        }
+
+        warnings
     }
 }
 
+/// Get the lowercase domain of an email address, if any.
+fn domain(address: &str) -> Option<String> {
+    address
+        .rsplit_once('@')
+        .map(|(_, domain)| domain.trim().trim_end_matches('>').to_lowercase())
+        .filter(|domain| !domain.is_empty())
+}
+
+/// SMTP authentication mechanisms.
+///
+/// Some servers advertise mechanisms they then reject,
+/// so the mechanism can be pinned explicitly in the config.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpAuthMechanism {
+    /// `PLAIN` authentication.
+    Plain,
+    /// `LOGIN` authentication.
+    Login,
+    /// `XOAUTH2` authentication.
+    Xoauth2,
+}
+
+/// How the emails are sent.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum EmailTransport {
+    /// Through the configured SMTP server.
+    #[default]
+    Smtp,
+    /// Through the local `sendmail` command.
+    Sendmail,
+    /// Written as `.eml` files to the `file_transport_dir`, nothing is sent.
+    ///
+    /// For tests and demos.
+    File,
+}
+
+/// How the connection to the SMTP server is secured.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum TlsMode {
+    /// Implicit TLS from the start of the connection, usually on port 465.
+    Wrapper,
+    /// Upgrade the connection with `STARTTLS`, failing if the server
+    /// doesn't support it, usually on port 587.
+    #[default]
+    StartTls,
+    /// Upgrade the connection with `STARTTLS` if the server supports it,
+    /// otherwise send in plain text.
+    Opportunistic,
+    /// No TLS at all, everything including the password is sent in
+    /// plain text.
+    ///
+    /// Only for test setups or trusted local relays.
+    None,
+}
+
 /// Configuration errors
 #[derive(Error, Debug)]
 pub enum ConfigError {
@@ -81,15 +900,168 @@ pub enum ConfigError {
     /// TOML deserialization
     #[error(transparent)]
     TomlDerError(#[from] DerTomlError),
+    /// JSON (de)serialization
+    #[error(transparent)]
+    JsonError(#[from] ureq::serde_json::Error),
+    /// YAML (de)serialization
+    #[error(transparent)]
+    YamlError(#[from] serde_yaml::Error),
+    /// The extension of the config file isn't a supported format
+    #[error("unsupported config file extension {0:?}, expected toml, json, yaml or yml")]
+    UnsupportedFormat(String),
+    /// The selected profile isn't in the config, see [`load_profile`]
+    #[error("unknown profile {0:?}, add it as a [profiles.{0}] table")]
+    UnknownProfile(String),
+    /// The config file to generate already exists, see [`generate_config`]
+    #[error("the config {0} already exists")]
+    AlreadyExists(PathBuf),
+    /// The config directory isn't writable
+    #[error(
+        "the config directory {0} isn't writable, \
+         set {CONFIG_PATH_ENV} to a config file in a writable location"
+    )]
+    ReadOnly(PathBuf),
+    /// The config is invalid, see [`Config::validate`]
+    #[error("invalid config: {0}")]
+    Invalid(String),
+    /// An environment variable override has an invalid value
+    #[error("invalid value of the {0} environment variable: {1}")]
+    EnvVarError(String, String),
+}
+
+/// Environment variable that overrides the path of the config file.
+pub const CONFIG_PATH_ENV: &str = "DMS_CONFIG_PATH";
+
+/// Environment variable that selects the profile of the config,
+/// see [`load_profile`].
+pub const PROFILE_ENV: &str = "DMS_PROFILE";
+
+/// The profile used when none is selected.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// The table of the config with the profiles by name.
+const PROFILES: &str = "profiles";
+
+/// Prefix of the environment variables that override config fields,
+/// e.g. `DMS_SMTP_SERVER` for `smtp_server`.
+pub const ENV_PREFIX: &str = "DMS_";
+
+/// Turn an IO error into [`ConfigError::ReadOnly`] if it's because `dir`
+/// isn't writable.
+fn read_only_error(error: std::io::Error, dir: &Path) -> ConfigError {
+    // EROFS, the same on Linux and macOS
+    const READ_ONLY_FILESYSTEM: i32 = 30;
+
+    let read_only = error.kind() == ErrorKind::PermissionDenied
+        || (cfg!(unix) && error.raw_os_error() == Some(READ_ONLY_FILESYSTEM));
+    if read_only {
+        ConfigError::ReadOnly(dir.to_path_buf())
+    } else {
+        ConfigError::IoError(error)
+    }
+}
+
+/// The keys of the TUI actions.
+///
+/// Each key is a single character, or `Enter`, `Esc`, `Space`, `Tab` or
+/// `Backspace`.
+/// An invalid key falls back to the default, with a warning.
+/// If two actions share a key, or one uses `Esc` or `y`, which confirms
+/// quitting, all the keys fall back to the defaults, with a warning.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct Keybindings {
+    /// Check in, `c` by default.
+    pub check_in: String,
+    /// Quit, `q` by default. `Esc` always quits too.
+    pub quit: String,
+    /// Pause or resume the countdown, `p` by default.
+    pub pause: String,
+    /// Enable or disable the switch, `e` by default.
+    pub toggle: String,
+    /// Send the test email, `t` by default.
+    pub test_email: String,
+    /// Extend the deadline by a duration typed in, `x` by default.
+    pub extend: String,
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        Keybindings {
+            check_in: "c".to_string(),
+            quit: "q".to_string(),
+            pause: "p".to_string(),
+            toggle: "e".to_string(),
+            test_email: "t".to_string(),
+            extend: "x".to_string(),
+        }
+    }
+}
+
+/// An escalation stage between the warning and the dead man's email.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Escalation {
+    /// Timer in seconds after the previous email before this one is sent.
+    ///
+    /// Also accepts a human-readable duration, e.g. `"3d"`.
+    #[serde(deserialize_with = "duration", serialize_with = "human_duration")]
+    #[cfg_attr(feature = "schema", schemars(with = "HumanDuration"))]
+    pub timer: u64,
+    /// The subject of the email.
+    pub subject: String,
+    /// The message of the email.
+    pub message: String,
+    /// Who gets the email, comma-separated.
+    ///
+    /// If not set, it goes to the `from` address, like the warning.
+    #[serde(default)]
+    pub to: Option<String>,
+}
+
+/// A dead man's email of its own, e.g. a letter for a single person.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PersonalEmail {
+    /// Who gets the email, comma-separated.
+    pub to: String,
+    /// The message of the email.
+    ///
+    /// If not set, the dead man's `message`, with its HTML version.
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Attachments to send with the email, instead of the `attachments`.
+    ///
+    /// Accepts a single path or a list of paths.
+    #[serde(
+        default,
+        alias = "attachment",
+        deserialize_with = "one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub attachments: Vec<PathBuf>,
 }
 
 /// Enum to represent the type of email to send.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Email {
     /// Send the warning email.
     Warning,
+    /// Send the email of the escalation stage with this index.
+    Escalation(usize),
     /// Send the dead man's email.
     DeadMan,
+    /// Send the dead man's email of the personal email with this index.
+    Personal(usize),
+    /// Send a test email to check the email settings.
+    Test,
+    /// Send the final grace email, the last chance to check in.
+    FinalGrace,
+    /// Send the check-in confirmation email.
+    CheckInConfirmation,
+    /// Send the reminder email, before the warning.
+    Reminder,
 }
 
 /// Load the configuration from the OS-agnostic config directory.
@@ -104,8 +1076,13 @@ pub enum Email {
 ///
 /// ## Notes
 ///
+/// The [`CONFIG_PATH_ENV`] environment variable, if set, overrides the path.
 /// This function handles testing and non-testing environments.
 pub fn config_path() -> Result<PathBuf, ConfigError> {
+    if let Some(path) = std::env::var_os(CONFIG_PATH_ENV) {
+        return Ok(PathBuf::from(path));
+    }
+
     let base_dir = if cfg!(test) {
         // Use a temporary directory for tests
         std::env::temp_dir()
@@ -122,10 +1099,177 @@ pub fn config_path() -> Result<PathBuf, ConfigError> {
         "deadman"
     });
 
-    fs::create_dir_all(&config_dir).expect("Failed to create config directory");
+    fs::create_dir_all(&config_dir).map_err(|error| read_only_error(error, &config_dir))?;
     Ok(config_dir.join("config.toml"))
 }
 
+/// Get the path of the timer state file.
+///
+/// The state is stored next to the config file in the config directory.
+/// Each profile other than the [`DEFAULT_PROFILE`] has its own,
+/// e.g. `state-work.toml`, so their switches can run side by side.
+///
+/// ## Errors
+///
+/// - Fails if the config path cannot be determined
+/// - Fails if the selected profile is invalid, see [`profile`]
+pub fn state_path() -> Result<PathBuf, ConfigError> {
+    let file_name = match profile()?.as_str() {
+        DEFAULT_PROFILE => "state.toml".to_string(),
+        profile => format!("state-{profile}.toml"),
+    };
+    Ok(config_path()?.with_file_name(file_name))
+}
+
+/// Get the selected profile, from [`PROFILE_ENV`] or the
+/// [`DEFAULT_PROFILE`].
+///
+/// ## Errors
+///
+/// - Fails if the name isn't only ASCII letters, digits, `-` and `_`,
+///   since it names the state file.
+pub fn profile() -> Result<String, ConfigError> {
+    let profile = match std::env::var(PROFILE_ENV) {
+        Ok(profile) => profile,
+        Err(_) => return Ok(DEFAULT_PROFILE.to_string()),
+    };
+    let valid = |char: char| char.is_ascii_alphanumeric() || char == '-' || char == '_';
+    if profile.is_empty() || !profile.chars().all(valid) {
+        return Err(ConfigError::EnvVarError(
+            PROFILE_ENV.to_string(),
+            format!("{profile:?} isn't only letters, digits, - and _"),
+        ));
+    }
+    Ok(profile)
+}
+
+/// The JSON Schema of the [`Config`], pretty-printed.
+///
+/// Generated from the [`Config`] type, so it's always in sync.
+/// Secret fields say so in their description.
+#[cfg(feature = "schema")]
+pub fn config_schema() -> String {
+    let schema = schemars::schema_for!(Config);
+    serde_json::to_string_pretty(&schema).expect("the schema is valid JSON")
+}
+
+/// Get the path of the log of the emails of a dry run.
+///
+/// The log is stored next to the config file in the config directory.
+///
+/// ## Errors
+///
+/// - Fails if the config path cannot be determined
+pub fn dry_run_log_path() -> Result<PathBuf, ConfigError> {
+    Ok(config_path()?.with_file_name("dry_run.log"))
+}
+
+/// Append what a dry run didn't do to the log at `path`,
+/// see [`Config::dry_run`].
+///
+/// ## Errors
+///
+/// - If the log file cannot be written.
+pub fn log_dry_run(path: &Path, what: &str) -> io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "[{}] Dry run, {}", Local::now().to_rfc3339(), what)
+}
+
+/// Append what a dry run didn't do to the [`dry_run_log_path`].
+///
+/// ## Errors
+///
+/// - If the config path cannot be determined.
+/// - If the log file cannot be written.
+pub fn log_dry_run_skipped(what: &str) -> io::Result<()> {
+    let path = dry_run_log_path().map_err(|error| io::Error::new(ErrorKind::Other, error))?;
+    log_dry_run(&path, what)
+}
+
+/// Get the path of the log of the command hooks and the config wipe.
+///
+/// The log is stored next to the config file in the config directory.
+///
+/// ## Errors
+///
+/// - Fails if the config path cannot be determined
+pub fn hook_log_path() -> Result<PathBuf, ConfigError> {
+    Ok(config_path()?.with_file_name("hook.log"))
+}
+
+/// Overwrite the config at `path` with zeros and remove it.
+///
+/// Used by `wipe_config_on_fire`, so the credentials can't be reused
+/// once the switch fired.
+/// Overwriting is best effort: copy-on-write filesystems, SSDs and backups
+/// may still hold the old contents.
+///
+/// ## Errors
+///
+/// - If the config cannot be overwritten or removed.
+pub fn wipe_config(path: &Path) -> io::Result<()> {
+    let len = fs::metadata(path)?.len();
+    let mut file = OpenOptions::new().write(true).open(path)?;
+    io::copy(&mut io::repeat(0).take(len), &mut file)?;
+    file.sync_all()?;
+    drop(file);
+    fs::remove_file(path)
+}
+
+/// Wipe the `profile` from the config at `path`, see [`wipe_config`].
+///
+/// A profile with its own `[profiles.<name>]` table only has that table
+/// removed, so firing one switch leaves the other profiles of the file
+/// working. The top-level fields it inherits are kept for them.
+/// Otherwise, i.e. the [`DEFAULT_PROFILE`] without a table, the whole file
+/// is wiped.
+///
+/// ## Errors
+///
+/// - If the config cannot be parsed, rewritten or wiped.
+pub fn wipe_profile(path: &Path, profile: &str) -> io::Result<()> {
+    let invalid = |error: ConfigError| io::Error::new(ErrorKind::InvalidData, error);
+    let format = ConfigFormat::from_path(path).map_err(invalid)?;
+    let mut file = format.parse(&fs::read_to_string(path)?).map_err(invalid)?;
+    let removed = file
+        .get_mut(PROFILES)
+        .and_then(toml::Value::as_table_mut)
+        .and_then(|profiles| profiles.remove(profile))
+        .is_some();
+    if !removed {
+        return wipe_config(path);
+    }
+
+    // Zero the old contents once the rest of the file replaced them
+    let mut old = OpenOptions::new().write(true).open(path)?;
+    let len = old.metadata()?.len();
+    let contents = format.to_string(&file).map_err(invalid)?;
+    write_atomically(path, contents.as_bytes()).map_err(invalid)?;
+    io::copy(&mut io::repeat(0).take(len), &mut old)?;
+    old.sync_all()
+}
+
+/// Append the outcome of wiping the `profile` from the config at `path`
+/// to the log file, see [`wipe_profile`].
+///
+/// ## Errors
+///
+/// - If the log file cannot be written.
+pub fn log_wipe(log: &Path, path: &Path, profile: &str, result: &io::Result<()>) -> io::Result<()> {
+    let entry = match result {
+        Ok(()) => format!(
+            "wiped the profile {profile:?} of the config {}",
+            path.display()
+        ),
+        Err(error) => format!(
+            "failed to wipe the profile {profile:?} of the config {}: {error}",
+            path.display()
+        ),
+    };
+    let mut file = OpenOptions::new().create(true).append(true).open(log)?;
+    writeln!(file, "[{}] {}", Local::now().to_rfc3339(), entry)
+}
+
 /// Save the configuration to the OS-agnostic config directory.
 ///
 /// Under the hood uses the [`directories_next`] crate to find the
@@ -135,13 +1279,157 @@ pub fn config_path() -> Result<PathBuf, ConfigError> {
 ///
 /// - Fails if the home directory cannot be found
 /// - Fails if the config directory cannot be created
+///
+/// ## Notes
+///
+/// The file is replaced atomically, so a crash mid-write never leaves
+/// a truncated config behind.
 pub fn save_config(config: &Config) -> Result<(), ConfigError> {
-    let config_path = config_path()?;
-    let mut file = File::create(config_path)?;
-    let config = toml::to_string(config)?;
+    save_profile(&config_path()?, &profile()?, config)
+}
+
+/// Save the config as the `profile` of the config file at `path`.
+///
+/// The other profiles are kept as they are. The [`DEFAULT_PROFILE`] is
+/// saved at the top level, unless the file has a table for it.
+/// A profile's table only gets the fields that differ from the top level,
+/// so it keeps inheriting the others.
+///
+/// ## Errors
+///
+/// - Fails if the existing file cannot be read or parsed.
+/// - Fails if the config cannot be serialized or written.
+pub fn save_profile(path: &Path, profile: &str, config: &Config) -> Result<(), ConfigError> {
+    let format = ConfigFormat::from_path(path)?;
+    let mut file = if path.exists() {
+        format.parse(&fs::read_to_string(path)?)?
+    } else {
+        toml::Value::Table(toml::value::Table::new())
+    };
+    let config = toml::Value::try_from(config)?;
+    let mut profiles = file.as_table_mut().and_then(|file| file.remove(PROFILES));
+
+    let has_table = profiles
+        .as_ref()
+        .and_then(|profiles| profiles.get(profile))
+        .is_some();
+    if profile == DEFAULT_PROFILE && !has_table {
+        file = config;
+    } else if let Some(profiles) = profiles
+        .get_or_insert_with(|| toml::Value::Table(toml::value::Table::new()))
+        .as_table_mut()
+    {
+        // Compare with the top level as it would be saved
+        let top_level: Config = file.clone().try_into()?;
+        let overrides = match (config, toml::Value::try_from(top_level)?) {
+            (toml::Value::Table(config), toml::Value::Table(top_level)) => toml::Value::Table(
+                config
+                    .into_iter()
+                    .filter(|(field, value)| top_level.get(field) != Some(value))
+                    .collect(),
+            ),
+            (config, _) => config,
+        };
+        profiles.insert(profile.to_string(), overrides);
+    }
+    if let (Some(file), Some(profiles)) = (file.as_table_mut(), profiles) {
+        file.insert(PROFILES.to_string(), profiles);
+    }
 
-    file.write_all(config.as_bytes())?;
+    write_atomically(path, format.to_string(&file)?.as_bytes())
+}
 
+/// Save whether the switch is `enabled` as the `profile` of the config
+/// file at `path`.
+///
+/// The rest of the profile is saved as it is in the file, so the `DMS_*`
+/// environment variables, see [`with_env_overrides`], are never written
+/// to it.
+///
+/// ## Errors
+///
+/// - Fails if the existing file cannot be read or parsed.
+/// - Fails if the config cannot be serialized or written.
+pub fn save_enabled(path: &Path, profile: &str, enabled: bool) -> Result<(), ConfigError> {
+    let config = Config {
+        enabled,
+        ..load_profile(path, profile)?
+    };
+    save_profile(path, profile, &config)
+}
+
+/// Write the default config to `path`, in the format of its extension,
+/// creating its directory if needed.
+///
+/// TOML and YAML configs start with a comment pointing to the docs.
+///
+/// ## Errors
+///
+/// - [`ConfigError::AlreadyExists`] if there is a file at `path`,
+///   unless `force`.
+/// - Fails if the config cannot be written.
+pub fn generate_config(path: &Path, force: bool) -> Result<(), ConfigError> {
+    if path.exists() && !force {
+        return Err(ConfigError::AlreadyExists(path.to_path_buf()));
+    }
+    let format = ConfigFormat::from_path(path)?;
+    let mut config = match format {
+        ConfigFormat::Toml | ConfigFormat::Yaml => {
+            "# The Dead Man's Switch config, see the README for what each field does:\n\
+             # https://github.com/storopoli/dead-man-switch\n"
+                .to_string()
+        }
+        ConfigFormat::Json => String::new(),
+    };
+    config.push_str(&format.to_string(&toml::Value::try_from(Config::default())?)?);
+
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).map_err(|error| read_only_error(error, dir))?;
+    }
+    write_atomically(path, config.as_bytes())
+}
+
+/// Write `contents` to `path` atomically.
+///
+/// The contents are written and synced to a uniquely named temporary file
+/// in the same directory, which is then renamed over `path`.
+/// Renames are atomic on the same filesystem, so `path` either has the old
+/// or the new contents, never a partial write.
+///
+/// The temporary file keeps the permissions of the file it replaces,
+/// e.g. of a config holding the password, and is only readable by the
+/// user for a new file.
+///
+/// ## Errors
+///
+/// - [`ConfigError::ReadOnly`] if the directory of `path` isn't writable.
+pub(crate) fn write_atomically(path: &Path, contents: &[u8]) -> Result<(), ConfigError> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+
+    let write = || -> Result<(), std::io::Error> {
+        let mut file = NamedTempFile::new_in(dir)?;
+        if let Ok(metadata) = fs::metadata(path) {
+            file.as_file().set_permissions(metadata.permissions())?;
+        }
+        file.write_all(contents)?;
+        file.as_file().sync_all()?;
+        file.persist(path).map_err(|error| error.error)?;
+        sync_dir(dir)
+    };
+    write().map_err(|error| read_only_error(error, dir))
+}
+
+/// Sync the directory `dir`, so a rename in it survives a crash.
+///
+/// Only on Unix, directories can't be opened to be synced on Windows.
+fn sync_dir(dir: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    File::open(dir)?.sync_all()?;
+    #[cfg(not(unix))]
+    let _ = dir;
     Ok(())
 }
 
@@ -161,18 +1449,224 @@ pub fn save_config(config: &Config) -> Result<(), ConfigError> {
 /// use dead_man_switch::config::load_or_initialize_config;
 /// let config = load_or_initialize_config().unwrap();
 /// ```
+///
+/// ## Notes
+///
+/// An existing config is only read, never written back,
+/// so a read-only config works.
+///
+/// Fields are then overridden by the `DMS_*` environment variables,
+/// see [`with_env_overrides`], and the result is [`Config::validate`]d.
 pub fn load_or_initialize_config() -> Result<Config, ConfigError> {
     let config_path = config_path()?;
-    if !config_path.exists() {
+    let config = if !config_path.exists() {
         let config = Config::default();
         save_config(&config)?;
 
-        Ok(config)
+        config
     } else {
-        let config = fs::read_to_string(&config_path)?;
-        let config: Config = toml::from_str(&config)?;
+        load_config(&config_path)?
+    };
+    let config = with_env_overrides(config, std::env::vars())?;
+    config.validate()?;
 
-        Ok(config)
+    Ok(config)
+}
+
+/// Override the config fields with the matching environment variables,
+/// e.g. `DMS_SMTP_SERVER` for `smtp_server`.
+///
+/// Only top-level fields can be overridden, e.g. not the `signal` table.
+/// Variables that don't match a field are ignored.
+///
+/// ## Errors
+///
+/// - Fails if a value cannot be converted to the type of its field,
+///   naming the variable.
+pub fn with_env_overrides(
+    mut config: Config,
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> Result<Config, ConfigError> {
+    let mut table = match toml::Value::try_from(&config)? {
+        toml::Value::Table(table) => table,
+        _ => unreachable!("the config is a table"),
+    };
+
+    for (var, value) in vars {
+        let field = match var.strip_prefix(ENV_PREFIX) {
+            Some(field) if var != CONFIG_PATH_ENV => field.to_lowercase(),
+            _ => continue,
+        };
+        let invalid = |error: &dyn std::fmt::Display| {
+            ConfigError::EnvVarError(var.clone(), error.to_string())
+        };
+        // Convert to the type of the current value
+        let candidates = match table.get(&field) {
+            Some(toml::Value::Integer(_)) => vec![toml::Value::Integer(
+                value.trim().parse().map_err(|error| invalid(&error))?,
+            )],
+            Some(toml::Value::Boolean(_)) => vec![toml::Value::Boolean(
+                value.trim().parse().map_err(|error| invalid(&error))?,
+            )],
+            Some(_) => vec![toml::Value::String(value)],
+            // An unset field has no value to go by, e.g. an `Option<u64>`,
+            // so try an integer or a boolean before a string
+            None => {
+                let mut candidates = vec![];
+                if let Ok(integer) = value.trim().parse() {
+                    candidates.push(toml::Value::Integer(integer));
+                }
+                if let Ok(boolean) = value.trim().parse() {
+                    candidates.push(toml::Value::Boolean(boolean));
+                }
+                candidates.push(toml::Value::String(value));
+                candidates
+            }
+        };
+
+        // Check each variable on its own, so the error names it
+        let mut first_error = None;
+        let overridden = candidates.into_iter().find_map(|candidate| {
+            let mut overridden = table.clone();
+            overridden.insert(field.clone(), candidate);
+            match toml::Value::Table(overridden.clone()).try_into() {
+                Ok(config) => Some((config, overridden)),
+                Err(error) => {
+                    first_error.get_or_insert(error);
+                    None
+                }
+            }
+        });
+        match (overridden, first_error) {
+            (Some((overridden, overridden_table)), _) => {
+                config = overridden;
+                table = overridden_table;
+            }
+            (None, Some(error)) => return Err(invalid(&error)),
+            (None, None) => unreachable!("there is always a string candidate"),
+        }
+    }
+
+    Ok(config)
+}
+
+/// Load the configuration from `path`.
+///
+/// ## Errors
+///
+/// - Fails if the file cannot be read or parsed.
+pub fn load_config(path: &Path) -> Result<Config, ConfigError> {
+    load_profile(path, &profile()?)
+}
+
+/// Load the `profile` of the config file at `path`.
+///
+/// A profile is a `[profiles.<name>]` table whose fields replace the
+/// top-level ones, so it only needs the fields that differ.
+/// Without a table for it, the [`DEFAULT_PROFILE`] is the top level.
+///
+/// ## Errors
+///
+/// - Fails if the file cannot be read or parsed.
+/// - [`ConfigError::UnknownProfile`] if the file has no such profile.
+pub fn load_profile(path: &Path, profile: &str) -> Result<Config, ConfigError> {
+    let format = ConfigFormat::from_path(path)?;
+    let mut config = format.parse(&fs::read_to_string(path)?)?;
+
+    if let Some(config) = config.as_table_mut() {
+        let overrides = config
+            .remove(PROFILES)
+            .and_then(|mut profiles| profiles.as_table_mut()?.remove(profile));
+        match overrides {
+            Some(toml::Value::Table(overrides)) => config.extend(overrides),
+            Some(_) => {
+                return Err(ConfigError::Invalid(format!(
+                    "the profile {profile:?} isn't a table"
+                )))
+            }
+            None if profile == DEFAULT_PROFILE => {}
+            None => return Err(ConfigError::UnknownProfile(profile.to_string())),
+        }
+    }
+
+    Ok(config.try_into()?)
+}
+
+/// The format of a config file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    /// TOML, the default.
+    Toml,
+    /// JSON.
+    Json,
+    /// YAML.
+    Yaml,
+}
+
+impl ConfigFormat {
+    /// Get the format of the config file at `path` from its extension,
+    /// `.toml`, `.json`, `.yaml` or `.yml`.
+    ///
+    /// A path without an extension is TOML.
+    ///
+    /// ## Errors
+    ///
+    /// - [`ConfigError::UnsupportedFormat`] for any other extension.
+    pub fn from_path(path: &Path) -> Result<Self, ConfigError> {
+        let extension = match path.extension().and_then(OsStr::to_str) {
+            Some(extension) => extension,
+            None => return Ok(ConfigFormat::Toml),
+        };
+        match extension.to_ascii_lowercase().as_str() {
+            "toml" => Ok(ConfigFormat::Toml),
+            "json" => Ok(ConfigFormat::Json),
+            "yaml" | "yml" => Ok(ConfigFormat::Yaml),
+            _ => Err(ConfigError::UnsupportedFormat(extension.to_string())),
+        }
+    }
+
+    /// Serialize a config in this format.
+    ///
+    /// ## Errors
+    ///
+    /// - Fails if the config cannot be serialized.
+    pub fn to_string<T: Serialize>(self, config: &T) -> Result<String, ConfigError> {
+        Ok(match self {
+            ConfigFormat::Toml => toml::to_string(config)?,
+            ConfigFormat::Json => ureq::serde_json::to_string_pretty(config)?,
+            ConfigFormat::Yaml => serde_yaml::to_string(config)?,
+        })
+    }
+
+    /// Parse a config in this format into a TOML value, so that every
+    /// format is handled alike.
+    ///
+    /// JSON and YAML `null`s are dropped, as TOML has none:
+    /// a missing field means the same.
+    ///
+    /// ## Errors
+    ///
+    /// - Fails if the config is invalid in this format.
+    pub fn parse(self, config: &str) -> Result<toml::Value, ConfigError> {
+        let mut value: ureq::serde_json::Value = match self {
+            ConfigFormat::Toml => return Ok(toml::from_str(config)?),
+            ConfigFormat::Json => ureq::serde_json::from_str(config)?,
+            ConfigFormat::Yaml => serde_yaml::from_str(config)?,
+        };
+        remove_nulls(&mut value);
+        Ok(toml::Value::try_from(value)?)
+    }
+}
+
+/// Remove the `null` fields of the JSON `value`, recursively.
+fn remove_nulls(value: &mut ureq::serde_json::Value) {
+    match value {
+        ureq::serde_json::Value::Object(fields) => {
+            fields.retain(|_, value| !value.is_null());
+            fields.values_mut().for_each(remove_nulls);
+        }
+        ureq::serde_json::Value::Array(items) => items.iter_mut().for_each(remove_nulls),
+        _ => {}
     }
 }
 
@@ -196,6 +1690,571 @@ mod test {
         teardown();
     }
 
+    #[test]
+    fn test_config_formats() {
+        let format = |path: &str| ConfigFormat::from_path(Path::new(path));
+        assert_eq!(format("config.toml").unwrap(), ConfigFormat::Toml);
+        assert_eq!(format("config").unwrap(), ConfigFormat::Toml);
+        assert_eq!(format("config.json").unwrap(), ConfigFormat::Json);
+        assert_eq!(format("config.YAML").unwrap(), ConfigFormat::Yaml);
+        assert_eq!(format("config.yml").unwrap(), ConfigFormat::Yaml);
+        assert!(matches!(
+            format("config.ini"),
+            Err(ConfigError::UnsupportedFormat(extension)) if extension == "ini"
+        ));
+    }
+
+    #[test]
+    fn test_load_json_and_yaml() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            escalations: vec![Escalation {
+                timer: 60,
+                subject: "Urgent".to_string(),
+                message: "Check in now".to_string(),
+                to: None,
+            }],
+            ..Config::default()
+        };
+        for (name, format) in [
+            ("config.json", ConfigFormat::Json),
+            ("config.yaml", ConfigFormat::Yaml),
+        ] {
+            let path = dir.path().join(name);
+            fs::write(&path, format.to_string(&config).unwrap()).unwrap();
+            assert_eq!(load_config(&path).unwrap(), config);
+        }
+
+        // Human-readable durations work in every format
+        let yaml = ConfigFormat::Yaml.to_string(&Config::default()).unwrap();
+        assert!(yaml.contains("timer_warning: 2w"));
+    }
+
+    #[test]
+    fn test_profiles() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let base = Config::default();
+        let mut file = toml::Value::try_from(&base).unwrap();
+        file.as_table_mut().unwrap().insert(
+            PROFILES.to_string(),
+            toml::from_str("[work]\ntimer_warning = \"1d\"\nto = \"boss@example.com\"").unwrap(),
+        );
+        fs::write(&path, toml::to_string(&file).unwrap()).unwrap();
+
+        let work = load_profile(&path, "work").unwrap();
+        assert_eq!(work.timer_warning, 60 * 60 * 24);
+        assert_eq!(work.to, "boss@example.com");
+        assert_eq!(work.from, base.from);
+        assert_eq!(load_profile(&path, DEFAULT_PROFILE).unwrap(), base);
+        assert!(matches!(
+            load_profile(&path, "home"),
+            Err(ConfigError::UnknownProfile(profile)) if profile == "home"
+        ));
+
+        // Saving a profile keeps the others
+        let disabled = Config {
+            enabled: false,
+            ..work.clone()
+        };
+        save_profile(&path, "work", &disabled).unwrap();
+        assert_eq!(load_profile(&path, "work").unwrap(), disabled);
+        assert_eq!(load_profile(&path, DEFAULT_PROFILE).unwrap(), base);
+        // Only with the fields that differ, the others are still inherited
+        let file: toml::Value = toml::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        let mut fields: Vec<_> = file[PROFILES]["work"].as_table().unwrap().keys().collect();
+        fields.sort();
+        assert_eq!(fields, ["enabled", "timer_warning", "to"]);
+        save_profile(&path, DEFAULT_PROFILE, &disabled).unwrap();
+        assert_eq!(load_profile(&path, DEFAULT_PROFILE).unwrap(), disabled);
+        assert_eq!(load_profile(&path, "work").unwrap(), disabled);
+    }
+
+    #[test]
+    fn test_save_enabled() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        generate_config(&path, false).unwrap();
+
+        // The overridden config isn't what gets saved
+        let overridden = with_env_overrides(
+            load_profile(&path, DEFAULT_PROFILE).unwrap(),
+            [("DMS_PASSWORD".to_string(), "hunter2".to_string())],
+        )
+        .unwrap();
+        assert_eq!(overridden.password, "hunter2");
+        save_enabled(&path, DEFAULT_PROFILE, false).unwrap();
+
+        let saved = load_profile(&path, DEFAULT_PROFILE).unwrap();
+        assert!(!saved.enabled);
+        assert_eq!(
+            saved,
+            Config {
+                enabled: false,
+                ..Config::default()
+            }
+        );
+        assert!(!fs::read_to_string(&path).unwrap().contains("hunter2"));
+    }
+
+    #[test]
+    fn test_generate_config() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["config.toml", "config.json", "config.yml"] {
+            let path = dir.path().join("nested").join(name);
+            generate_config(&path, false).unwrap();
+            assert_eq!(
+                load_profile(&path, DEFAULT_PROFILE).unwrap(),
+                Config::default()
+            );
+
+            // An existing config is only overwritten when forced
+            assert!(matches!(
+                generate_config(&path, false),
+                Err(ConfigError::AlreadyExists(existing)) if existing == path
+            ));
+            generate_config(&path, true).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_personal_emails_round_trip() {
+        let config: Config = toml::from_str(&format!(
+            "{}\n[[personal_emails]]\nto = \"spouse@example.com\"\nattachment = \"letter.pdf\"\n",
+            toml::to_string(&Config::default()).unwrap()
+        ))
+        .unwrap();
+        assert_eq!(
+            config.personal_emails,
+            vec![PersonalEmail {
+                to: "spouse@example.com".to_string(),
+                message: None,
+                attachments: vec![PathBuf::from("letter.pdf")],
+            }]
+        );
+
+        // The personal emails can follow the escalations
+        let config = Config {
+            escalations: vec![Escalation {
+                timer: 60,
+                subject: "Urgent".to_string(),
+                message: "Check in now".to_string(),
+                to: None,
+            }],
+            ..config
+        };
+        let serialized = toml::to_string(&config).unwrap();
+        assert_eq!(toml::from_str::<Config>(&serialized).unwrap(), config);
+    }
+
+    #[test]
+    fn test_wipe_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let log = dir.path().join("hook.log");
+        generate_config(&path, false).unwrap();
+
+        let result = wipe_config(&path);
+        assert!(result.is_ok());
+        assert!(!path.exists());
+        log_wipe(&log, &path, DEFAULT_PROFILE, &result).unwrap();
+
+        // Nothing left to wipe
+        let result = wipe_config(&path);
+        assert!(result.is_err());
+        log_wipe(&log, &path, DEFAULT_PROFILE, &result).unwrap();
+
+        let log = fs::read_to_string(&log).unwrap();
+        assert!(log.contains("] wiped the profile \"default\" of the config"));
+        assert!(log.contains("] failed to wipe the profile \"default\" of the config"));
+    }
+
+    #[test]
+    fn test_wipe_profile() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let mut file = toml::Value::try_from(Config::default()).unwrap();
+        file.as_table_mut().unwrap().insert(
+            PROFILES.to_string(),
+            toml::from_str("[work]\nto = \"boss@example.com\"\n[home]\nto = \"mom@example.com\"")
+                .unwrap(),
+        );
+        fs::write(&path, toml::to_string(&file).unwrap()).unwrap();
+
+        // Firing a profile with its own table keeps the others
+        wipe_profile(&path, "work").unwrap();
+        assert!(matches!(
+            load_profile(&path, "work"),
+            Err(ConfigError::UnknownProfile(_))
+        ));
+        assert_eq!(load_profile(&path, "home").unwrap().to, "mom@example.com");
+        assert_eq!(
+            load_profile(&path, DEFAULT_PROFILE).unwrap(),
+            Config::default()
+        );
+
+        // The default profile is the whole file
+        wipe_profile(&path, DEFAULT_PROFILE).unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_tables_round_trip() {
+        let config = Config {
+            custom_headers: Some(
+                [("X-Priority".to_string(), "1".to_string())]
+                    .into_iter()
+                    .collect(),
+            ),
+            keybindings: Keybindings {
+                check_in: "Enter".to_string(),
+                ..Keybindings::default()
+            },
+            ..Config::default()
+        };
+        let serialized = toml::to_string(&config).unwrap();
+        assert_eq!(toml::from_str::<Config>(&serialized).unwrap(), config);
+
+        // Missing keybindings keep their defaults
+        let config: Config =
+            toml::from_str(&serialized.replace("check_in = \"Enter\"", "")).unwrap();
+        assert_eq!(config.keybindings, Keybindings::default());
+    }
+
+    #[test]
+    fn test_warnings() {
+        let mut config = Config {
+            username: "me@gmail.com".to_string(),
+            from: "me@gmail.com".to_string(),
+            smtp_server: "smtp.gmail.com".to_string(),
+            ..Config::default()
+        };
+        assert!(config.warnings().is_empty());
+
+        config.smtp_server = "smtp.myisp.net".to_string();
+        assert_eq!(config.warnings().len(), 1);
+
+        config.username = "me@myisp.net".to_string();
+        assert_eq!(config.warnings().len(), 2);
+
+        // Usernames aren't always email addresses
+        config.username = "me".to_string();
+        config.smtp_server = "gmail.com".to_string();
+        assert!(config.warnings().is_empty());
+
+        config.oauth2_token_command = Some("oauth2l fetch".to_string());
+        assert_eq!(config.warnings().len(), 1);
+        config.smtp_auth_mechanism = Some(SmtpAuthMechanism::Xoauth2);
+        assert!(config.warnings().is_empty());
+
+        config.telegram_bot_token = Some("123:abc".to_string());
+        assert_eq!(config.warnings().len(), 1);
+        config.telegram_chat_id = Some("42".to_string());
+        assert!(config.warnings().is_empty());
+
+        config.dry_run = true;
+        assert_eq!(config.warnings().len(), 1);
+    }
+
+    #[test]
+    fn test_write_atomically() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let config = toml::to_string(&Config::default()).unwrap();
+
+        write_atomically(&path, config.as_bytes()).unwrap();
+        // Simulate a crash mid-write leaving a truncated temporary file behind
+        let stale = dir.path().join("config.toml.tmp");
+        fs::write(&stale, &config[..config.len() / 2]).unwrap();
+
+        let loaded: Config = toml::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(loaded, Config::default());
+
+        // The next write doesn't trip over it, nor leave its own behind
+        write_atomically(&path, config.as_bytes()).unwrap();
+        fs::remove_file(&stale).unwrap();
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+        let loaded: Config = toml::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(loaded, Config::default());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_atomically_keeps_the_mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+
+        // A new file is only readable by the user
+        let path = dir.path().join("config.toml");
+        write_atomically(&path, b"password = \"secret\"").unwrap();
+        assert_eq!(mode(&path), 0o600);
+
+        // An existing one keeps its mode
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+        write_atomically(&path, b"password = \"other\"").unwrap();
+        assert_eq!(mode(&path), 0o640);
+    }
+
+    #[test]
+    fn test_load_read_only_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(&path, toml::to_string(&Config::default()).unwrap()).unwrap();
+        let mut permissions = fs::metadata(&path).unwrap().permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(&path, permissions.clone()).unwrap();
+
+        let config = load_config(&path).unwrap();
+        assert_eq!(config, Config::default());
+
+        // So the directory can be removed on every platform
+        #[allow(clippy::permissions_set_readonly_false)]
+        permissions.set_readonly(false);
+        fs::set_permissions(&path, permissions).unwrap();
+    }
+
+    #[test]
+    fn test_read_only_error() {
+        let dir = Path::new("/etc/deadman");
+        let error = read_only_error(std::io::Error::from(ErrorKind::PermissionDenied), dir);
+        assert!(matches!(&error, ConfigError::ReadOnly(path) if path == dir));
+        assert!(error.to_string().contains(CONFIG_PATH_ENV));
+
+        let error = read_only_error(std::io::Error::from(ErrorKind::NotFound), dir);
+        assert!(matches!(error, ConfigError::IoError(_)));
+    }
+
+    #[cfg(feature = "schema")]
+    #[test]
+    fn test_config_schema() {
+        let schema: serde_json::Value = serde_json::from_str(&config_schema()).unwrap();
+        let properties = &schema["properties"];
+        assert!(properties["smtp_server"].is_object());
+        assert!(properties["password"]["description"]
+            .as_str()
+            .unwrap()
+            .contains("Secret"));
+        assert!(schema["required"]
+            .as_array()
+            .unwrap()
+            .contains(&"username".into()));
+    }
+
+    #[test]
+    fn test_human_durations() {
+        assert_eq!(parse_duration("14d"), Some(14 * 24 * 60 * 60));
+        assert_eq!(parse_duration("2w"), Some(2 * 7 * 24 * 60 * 60));
+        assert_eq!(parse_duration(" 36 h"), Some(36 * 60 * 60));
+        assert_eq!(parse_duration("90"), Some(90));
+        assert_eq!(parse_duration("3x"), None);
+        assert_eq!(parse_duration("d"), None);
+        assert_eq!(parse_duration(""), None);
+
+        let config = toml::to_string(&Config::default()).unwrap();
+        assert!(config.contains("timer_warning = \"2w\""));
+        assert!(config.contains("timer_dead_man = \"1w\""));
+        assert_eq!(
+            toml::from_str::<Config>(&config).unwrap(),
+            Config::default()
+        );
+
+        // Bare seconds are still accepted
+        let seconds = config.replace("\"2w\"", "1209600");
+        assert_eq!(
+            toml::from_str::<Config>(&seconds).unwrap(),
+            Config::default()
+        );
+
+        let invalid = config.replace("\"2w\"", "\"2 weeks\"");
+        let error = toml::from_str::<Config>(&invalid).unwrap_err().to_string();
+        assert!(error.contains("invalid duration \"2 weeks\""));
+        assert!(error.contains("timer_warning"));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(Config::default().validate().is_ok());
+
+        let invalid = |config: Config| match config.validate() {
+            Err(ConfigError::Invalid(problem)) => problem,
+            result => panic!("expected an invalid config, got {result:?}"),
+        };
+        let problem = invalid(Config {
+            smtp_server: " ".to_string(),
+            ..Config::default()
+        });
+        assert!(problem.contains("smtp_server"));
+        let problem = invalid(Config {
+            smtp_port: 0,
+            ..Config::default()
+        });
+        assert!(problem.contains("smtp_port"));
+        let problem = invalid(Config {
+            from: "me".to_string(),
+            ..Config::default()
+        });
+        assert!(problem.contains("from"));
+        let problem = invalid(Config {
+            to: "someone@example.com, nobody".to_string(),
+            ..Config::default()
+        });
+        assert!(problem.contains("nobody"));
+        let problem = invalid(Config {
+            reply_to: Some(String::new()),
+            ..Config::default()
+        });
+        assert!(problem.contains("reply_to"));
+        let problem = invalid(Config {
+            timer_warning: 0,
+            ..Config::default()
+        });
+        assert!(problem.contains("timer_warning"));
+        let problem = invalid(Config {
+            timer_dead_man: 0,
+            ..Config::default()
+        });
+        assert!(problem.contains("timer_dead_man"));
+        let problem = invalid(Config {
+            heartbeat_interval_seconds: 0,
+            ..Config::default()
+        });
+        assert!(problem.contains("heartbeat_interval_seconds"));
+        let problem = invalid(Config {
+            tui_refresh_ms: 0,
+            ..Config::default()
+        });
+        assert!(problem.contains("tui_refresh_ms"));
+        let problem = invalid(Config {
+            dead_man_resends: vec![86400],
+            ..Config::default()
+        });
+        assert!(problem.contains("public_url"));
+        let problem = invalid(Config {
+            warning_threshold_percent: 50,
+            caution_threshold_percent: 40,
+            ..Config::default()
+        });
+        assert!(problem.contains("warning_threshold_percent"));
+        let problem = invalid(Config {
+            caution_threshold_percent: 101,
+            ..Config::default()
+        });
+        assert!(problem.contains("caution_threshold_percent"));
+
+        let config = Config {
+            transport: EmailTransport::File,
+            smtp_server: String::new(),
+            ..Config::default()
+        };
+        assert!(invalid(config.clone()).contains("file_transport_dir"));
+        assert!(Config {
+            file_transport_dir: Some(std::env::temp_dir()),
+            ..config
+        }
+        .validate()
+        .is_ok());
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let vars = |vars: &[(&str, &str)]| -> Vec<(String, String)> {
+            vars.iter()
+                .map(|(var, value)| (var.to_string(), value.to_string()))
+                .collect()
+        };
+
+        let config = with_env_overrides(
+            Config::default(),
+            vars(&[
+                ("DMS_SMTP_SERVER", "smtp.gmail.com"),
+                ("DMS_SMTP_PORT", "465"),
+                ("DMS_TIMER_WARNING", "3d"),
+                ("DMS_ENABLED", "false"),
+                ("DMS_CC", "lawyer@example.com"),
+                ("DMS_REMINDER_BEFORE_SECS", "60"),
+                ("DMS_TELEGRAM_CHAT_ID", "123456789"),
+                ("DMS_CONFIG_PATH", "/etc/dms/config.toml"),
+                ("DMS_UNKNOWN", "ignored"),
+                ("SMTP_PORT", "not ours"),
+            ]),
+        )
+        .unwrap();
+        assert_eq!(config.smtp_server, "smtp.gmail.com");
+        assert_eq!(config.smtp_port, 465);
+        assert_eq!(config.timer_warning, 3 * 24 * 60 * 60);
+        assert!(!config.enabled);
+        assert_eq!(config.cc.as_deref(), Some("lawyer@example.com"));
+        // Unset optional fields get the type they take
+        assert_eq!(config.reminder_before_secs, Some(60));
+        assert_eq!(config.telegram_chat_id.as_deref(), Some("123456789"));
+
+        let error =
+            with_env_overrides(Config::default(), vars(&[("DMS_SMTP_PORT", "smtp")])).unwrap_err();
+        assert!(matches!(&error, ConfigError::EnvVarError(var, _) if var == "DMS_SMTP_PORT"));
+
+        let error =
+            with_env_overrides(Config::default(), vars(&[("DMS_TLS_MODE", "ssl")])).unwrap_err();
+        assert!(matches!(&error, ConfigError::EnvVarError(var, _) if var == "DMS_TLS_MODE"));
+
+        let error = with_env_overrides(
+            Config::default(),
+            vars(&[("DMS_REMINDER_BEFORE_SECS", "soon")]),
+        )
+        .unwrap_err();
+        assert!(
+            matches!(&error, ConfigError::EnvVarError(var, _) if var == "DMS_REMINDER_BEFORE_SECS")
+        );
+    }
+
+    #[test]
+    fn test_attachments_one_or_many() {
+        let config = toml::to_string(&Config::default()).unwrap();
+        let config: Config = toml::from_str(&config).unwrap();
+        assert!(config.attachments.is_empty());
+
+        let with_attachments = |attachments: &str| -> Config {
+            let config = toml::to_string(&Config::default()).unwrap();
+            let config = config.replace("attachments = []", attachments);
+            toml::from_str(&config).unwrap()
+        };
+        let config = with_attachments("attachment = \"/root/will.pdf\"");
+        assert_eq!(config.attachments, vec![PathBuf::from("/root/will.pdf")]);
+
+        let config = with_attachments("attachments = [\"/root/will.pdf\", \"/root/keys.gpg\"]");
+        assert_eq!(
+            config.attachments,
+            vec![
+                PathBuf::from("/root/will.pdf"),
+                PathBuf::from("/root/keys.gpg")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_smtp_auth_mechanism() {
+        let config = toml::to_string(&Config::default()).unwrap();
+
+        let login = format!("smtp_auth_mechanism = \"login\"\n{config}");
+        let login: Config = toml::from_str(&login).unwrap();
+        assert_eq!(login.smtp_auth_mechanism, Some(SmtpAuthMechanism::Login));
+
+        let unknown = format!("smtp_auth_mechanism = \"cram-md5\"\n{config}");
+        assert!(toml::from_str::<Config>(&unknown).is_err());
+    }
+
+    #[test]
+    fn test_tls_mode() {
+        let config = toml::to_string(&Config::default()).unwrap();
+        let default: Config =
+            toml::from_str(&config.replace("tls_mode = \"start_tls\"\n", "")).unwrap();
+        assert_eq!(default.tls_mode, TlsMode::StartTls);
+
+        let wrapper: Config = toml::from_str(&config.replace("start_tls", "wrapper")).unwrap();
+        assert_eq!(wrapper.tls_mode, TlsMode::Wrapper);
+    }
+
     #[test]
     fn test_load_or_initialize_config() {
         let config = Config::default();
@@ -1,26 +1,67 @@
 //! Email sending capabilities of the Dead Man's Switch.
 
+use std::collections::HashMap;
+use std::fmt;
 use std::fs;
-use std::io::{Error as IoError, ErrorKind as IoErrorKind};
+use std::io::{Cursor, Error as IoError, ErrorKind as IoErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
 use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
 
 use lettre::{
     address::AddressError,
     error::Error as LettreError,
     message::{
-        header::{ContentType, ContentTypeErr},
-        Attachment, Mailbox, MultiPart, SinglePart,
+        header::{
+            Cc, ContentType, ContentTypeErr, Header, HeaderName, HeaderValue, Headers, Subject, To,
+        },
+        Attachment, Mailbox, Mailboxes, MultiPart, SinglePart,
     },
-    transport::smtp::{
-        self,
-        authentication::Credentials,
-        client::{Tls, TlsParameters},
+    transport::{
+        file,
+        sendmail::{self, SendmailTransport},
+        smtp::{
+            self,
+            authentication::{Credentials, Mechanism},
+            client::{Tls, TlsParameters},
+        },
     },
-    Address, Message, SmtpTransport, Transport,
+    Address, FileTransport, Message, SmtpTransport, Transport,
+};
+#[cfg(feature = "async")]
+use lettre::{
+    AsyncFileTransport, AsyncSendmailTransport, AsyncSmtpTransport, AsyncTransport, Tokio1Executor,
 };
 use thiserror::Error;
+use zip::{result::ZipError, write::FileOptions, CompressionMethod, ZipWriter};
+
+use chrono::{Duration as ChronoDuration, Local};
+
+/// Build the SMTP `$transport` of the server configured in `$config`,
+/// returning early on an [`EmailError`].
+///
+/// A macro because the builders of the sync and async transports
+/// share no trait.
+macro_rules! smtp_transport {
+    ($config:expr, $transport:ty) => {{
+        let config: &Config = $config;
+        let credentials = Credentials::new(config.username.clone(), config.smtp_secret()?);
+        let mut builder = <$transport>::relay(&config.smtp_server)?
+            .port(config.smtp_port)
+            .credentials(credentials)
+            .tls(config.tls()?);
+        if let Some(mechanism) = config.smtp_auth_mechanism {
+            builder = builder.authentication(vec![mechanism.into()]);
+        }
+        builder.build()
+    }};
+}
 
-use crate::config::{Config, Email};
+use crate::config::{dry_run_log_path, Config, Email, EmailTransport, SmtpAuthMechanism, TlsMode};
+use crate::hook::{run_command, run_command_raw};
+use crate::timer::format_duration_exact;
 
 /// Errors that can occur when sending an email.
 #[derive(Error, Debug)]
@@ -28,6 +69,12 @@ pub enum EmailError {
     /// TLS error when sending the email.
     #[error(transparent)]
     TlsError(#[from] smtp::Error),
+    /// Error when sending the email with `sendmail`.
+    #[error(transparent)]
+    SendmailError(#[from] sendmail::Error),
+    /// Error when writing the email with the `file` transport.
+    #[error(transparent)]
+    FileError(#[from] file::Error),
     /// Error when parsing email addresses.
     #[error(transparent)]
     EmailError(#[from] AddressError),
@@ -40,6 +87,146 @@ pub enum EmailError {
     /// Error when determining the content type of the attachment.
     #[error(transparent)]
     InvalidContent(#[from] ContentTypeErr),
+    /// Error when parsing the recipients file.
+    #[error(transparent)]
+    RecipientsFileError(#[from] toml::de::Error),
+    /// A recipient group is not in the recipients file.
+    #[error("unknown recipient group: {0}")]
+    UnknownGroup(String),
+    /// A custom header has an invalid name or a multi-line value.
+    #[error("invalid custom header {0}")]
+    InvalidHeader(String),
+    /// An attachment is larger than the `max_attachment_bytes`.
+    #[error("attachment {0} is {1} bytes, over the max_attachment_bytes of {2}")]
+    AttachmentTooLarge(String, u64, u64),
+    /// A recipient address is invalid.
+    #[error("invalid recipient address {0}: {1}")]
+    InvalidRecipient(String, #[source] AddressError),
+    /// The SMTP server didn't accept the connection.
+    #[error("cannot connect to the SMTP server {0}")]
+    ConnectionFailed(String),
+    /// The `oauth2_token_command` failed or printed no token.
+    #[error("cannot get the OAuth2 token: {0}")]
+    OAuth2TokenError(String),
+    /// The `password_command` failed or printed no password.
+    #[error("cannot get the password: {0}")]
+    PasswordCommandError(String),
+    /// The SMTP server rejected the OAuth2 token, e.g. because it expired.
+    #[error("the SMTP server rejected the OAuth2 token, it may have expired: {0}")]
+    OAuth2TokenRejected(#[source] smtp::Error),
+    /// Error when zipping the attachments.
+    #[error(transparent)]
+    ZipError(#[from] ZipError),
+    /// The `attachment_command` failed.
+    #[error("cannot generate the attachment: {0}")]
+    AttachmentCommandError(String),
+    /// The personal email to these recipients failed.
+    #[error("the personal email to {0} failed: {1}")]
+    PersonalEmailFailed(String, #[source] Box<EmailError>),
+    /// Several of the emails failed.
+    #[error("{} emails failed: {}", .0.len(), join_errors(.0))]
+    Several(Vec<EmailError>),
+    /// Encrypting the email to the `pgp_public_key_path` failed.
+    #[error("cannot encrypt the email to the PGP key {0}: {1}")]
+    EncryptionError(String, String),
+}
+
+impl EmailError {
+    /// Whether only personal emails failed, see [`Config::personal_emails`],
+    /// so the dead man's email itself was sent.
+    ///
+    /// A failing personal email is reported, but doesn't fail the switch.
+    pub fn only_personal(&self) -> bool {
+        match self {
+            EmailError::PersonalEmailFailed(..) => true,
+            EmailError::Several(errors) => errors.iter().all(EmailError::only_personal),
+            _ => false,
+        }
+    }
+}
+
+/// The outcome of sending several emails, from their errors.
+fn failures(mut errors: Vec<EmailError>) -> Result<(), EmailError> {
+    match errors.len() {
+        0 => Ok(()),
+        1 => Err(errors.remove(0)),
+        _ => Err(EmailError::Several(errors)),
+    }
+}
+
+/// Name the recipients of a failed personal email.
+fn delivery_error(to: Option<&str>, error: EmailError) -> EmailError {
+    match to {
+        Some(to) => EmailError::PersonalEmailFailed(to.to_string(), Box::new(error)),
+        None => error,
+    }
+}
+
+/// Join the messages of the errors.
+fn join_errors(errors: &[EmailError]) -> String {
+    errors
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Delay before the first retry of a failed send, doubled on every retry.
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+
+/// Maximum total delay of the retries of a failed send.
+///
+/// Sending blocks the timer loop, so retrying a warning email must not
+/// hold off the dead man's timer for long.
+const RETRY_MAX_TOTAL_DELAY: Duration = Duration::from_secs(60);
+
+/// Maximum time the `oauth2_token_command` or the `password_command` may
+/// take to print the secret.
+const SECRET_COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Filename of the zipped attachments.
+const ZIP_FILENAME: &str = "attachments.zip";
+
+/// SMTP reply code of rejected credentials.
+const AUTHENTICATION_FAILED: &str = "535";
+
+/// Prefix of a reference to a recipient group in the recipients file.
+const GROUP_PREFIX: &str = "@group:";
+
+/// A preview of an email exactly as it would be sent.
+///
+/// Created with [`Config::preview_email`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmailPreview {
+    /// The subject of the email.
+    pub subject: String,
+    /// The `To` recipients.
+    pub to: Vec<String>,
+    /// The `Cc` recipients.
+    pub cc: Vec<String>,
+    /// The `Bcc` recipients.
+    pub bcc: Vec<String>,
+    /// The plain text body.
+    pub body: String,
+    /// The filenames of the attachments.
+    pub attachments: Vec<String>,
+}
+
+impl fmt::Display for EmailPreview {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Subject: {}", self.subject)?;
+        writeln!(f, "To: {}", self.to.join(", "))?;
+        if !self.cc.is_empty() {
+            writeln!(f, "Cc: {}", self.cc.join(", "))?;
+        }
+        if !self.bcc.is_empty() {
+            writeln!(f, "Bcc: {}", self.bcc.join(", "))?;
+        }
+        if !self.attachments.is_empty() {
+            writeln!(f, "Attachments: {}", self.attachments.join(", "))?;
+        }
+        write!(f, "\n{}", self.body)
+    }
 }
 
 impl Config {
@@ -50,94 +237,982 @@ impl Config {
     /// - If the email fails to send.
     /// - If the email cannot be created.
     /// - If the attachment cannot be read.
+    /// - If an attachment is larger than the `max_attachment_bytes`.
     ///
     /// ## Notes
     ///
     /// If the attachment MIME type cannot be determined, it will default to
     /// `application/octet-stream`.
+    ///
+    /// With `dry_run`, the email is built and appended to the
+    /// [`dry_run_log_path`] instead of being sent.
+    ///
+    /// The dead man's email also sends each of the `personal_emails`
+    /// separately, and a failing email doesn't prevent the others from
+    /// being sent.
+    /// If only personal emails failed, see [`EmailError::only_personal`],
+    /// the dead man's email itself was sent.
     pub fn send_email(&self, email_type: Email) -> Result<(), EmailError> {
+        let mut errors = vec![];
+        for (email_type, to) in self.emails_to_send(email_type) {
+            if let Err(error) = self.deliver(email_type) {
+                errors.push(delivery_error(to, error));
+            }
+        }
+        failures(errors)
+    }
+
+    /// Send a single email, see [`Config::send_email`].
+    fn deliver(&self, email_type: Email) -> Result<(), EmailError> {
+        if self.dry_run {
+            return self.log_dry_run(email_type);
+        }
         let email = self.create_email(email_type)?;
+        match self.transport {
+            EmailTransport::Smtp => {}
+            EmailTransport::Sendmail => {
+                SendmailTransport::new().send(&email)?;
+                return Ok(());
+            }
+            EmailTransport::File => {
+                FileTransport::new(self.file_transport_dir()?).send(&email)?;
+                return Ok(());
+            }
+        }
+        let mailer = self.mailer()?;
+
+        // Send the email, retrying transient failures
+        let mut delays = self.retry_delays();
+        loop {
+            match mailer.send(&email) {
+                Ok(_) => return Ok(()),
+                Err(error) => match delays.next() {
+                    Some(delay) if !error.is_permanent() => thread::sleep(delay),
+                    _ => return Err(self.smtp_error(error)),
+                },
+            }
+        }
+    }
+
+    /// Send the email asynchronously using the provided configuration.
+    ///
+    /// Same as [`Config::send_email`], but uses the Tokio SMTP transport
+    /// so it doesn't block an async runtime during SMTP I/O.
+    ///
+    /// ## Errors
+    ///
+    /// - If the email fails to send.
+    /// - If the email cannot be created.
+    /// - If the attachment cannot be read.
+    #[cfg(feature = "async")]
+    pub async fn send_email_async(&self, email_type: Email) -> Result<(), EmailError> {
+        let mut errors = vec![];
+        for (email_type, to) in self.emails_to_send(email_type) {
+            if let Err(error) = self.deliver_async(email_type).await {
+                errors.push(delivery_error(to, error));
+            }
+        }
+        failures(errors)
+    }
+
+    /// Send a single email asynchronously, see [`Config::send_email_async`].
+    #[cfg(feature = "async")]
+    async fn deliver_async(&self, email_type: Email) -> Result<(), EmailError> {
+        if self.dry_run {
+            return self.log_dry_run(email_type);
+        }
+        let email = self.create_email(email_type)?;
+        match self.transport {
+            EmailTransport::Smtp => {}
+            EmailTransport::Sendmail => {
+                AsyncSendmailTransport::<Tokio1Executor>::new()
+                    .send(email)
+                    .await?;
+                return Ok(());
+            }
+            EmailTransport::File => {
+                AsyncFileTransport::<Tokio1Executor>::new(self.file_transport_dir()?)
+                    .send(email)
+                    .await?;
+                return Ok(());
+            }
+        }
 
         // SMTP client setup
-        let creds = Credentials::new(self.username.clone(), self.password.clone());
-        let tls = TlsParameters::new_rustls(self.smtp_server.clone())?;
-        let mailer = SmtpTransport::relay(&self.smtp_server)?
-            .port(self.smtp_port)
-            .credentials(creds)
-            .tls(Tls::Required(tls))
-            .build();
-
-        // Send the email
-        mailer.send(&email)?;
+        let mailer = smtp_transport!(self, AsyncSmtpTransport<Tokio1Executor>);
+
+        // Send the email, retrying transient failures
+        let mut delays = self.retry_delays();
+        loop {
+            match mailer.send(email.clone()).await {
+                Ok(_) => return Ok(()),
+                Err(error) => match delays.next() {
+                    Some(delay) if !error.is_permanent() => tokio::time::sleep(delay).await,
+                    _ => return Err(self.smtp_error(error)),
+                },
+            }
+        }
+    }
+
+    /// The emails sent for the email type, with the recipients of the
+    /// personal emails.
+    ///
+    /// The dead man's email comes with the `personal_emails`.
+    fn emails_to_send(&self, email_type: Email) -> Vec<(Email, Option<&str>)> {
+        let mut emails = vec![(email_type, None)];
+        if email_type == Email::DeadMan {
+            emails.extend(
+                self.personal_emails
+                    .iter()
+                    .enumerate()
+                    .map(|(index, personal)| (Email::Personal(index), Some(personal.to.as_str()))),
+            );
+        }
+        emails
+    }
+
+    /// Send the test email to `from`, to check that the SMTP settings work.
+    ///
+    /// Uses the same transport as [`Config::send_email`].
+    ///
+    /// ## Errors
+    ///
+    /// - If the email fails to send, with the concrete SMTP error.
+    pub fn send_test_email(&self) -> Result<(), EmailError> {
+        self.send_email(Email::Test)
+    }
+
+    /// Check that the SMTP server accepts a connection with the configured
+    /// credentials, without sending anything.
+    ///
+    /// Meant to run at startup, so wrong settings show up right away
+    /// instead of when the switch fires.
+    /// There is nothing to check with the `sendmail` or `file` transports.
+    ///
+    /// ## Errors
+    ///
+    /// - If the connection or the authentication fails, with the concrete
+    ///   SMTP error.
+    pub fn check_smtp_connection(&self) -> Result<(), EmailError> {
+        if self.transport != EmailTransport::Smtp {
+            return Ok(());
+        }
+        let connected = self
+            .mailer()?
+            .test_connection()
+            .map_err(|error| self.smtp_error(error))?;
+        if connected {
+            Ok(())
+        } else {
+            Err(EmailError::ConnectionFailed(self.smtp_server.clone()))
+        }
+    }
+
+    /// Append the email to the [`dry_run_log_path`] instead of sending it.
+    ///
+    /// ## Errors
+    ///
+    /// - If the email cannot be created.
+    /// - If the log cannot be written.
+    fn log_dry_run(&self, email_type: Email) -> Result<(), EmailError> {
+        let path = dry_run_log_path().map_err(|error| IoError::new(IoErrorKind::Other, error))?;
+        log_dry_run(&path, &self.preview_email(email_type)?)?;
         Ok(())
     }
+
+    /// The directory of the `file` transport, created if missing.
+    ///
+    /// ## Errors
+    ///
+    /// - If the `file_transport_dir` is not set or cannot be created.
+    fn file_transport_dir(&self) -> Result<&Path, EmailError> {
+        let dir = self.file_transport_dir.as_deref().ok_or_else(|| {
+            IoError::new(IoErrorKind::NotFound, "`file_transport_dir` is not set")
+        })?;
+        fs::create_dir_all(dir)?;
+        Ok(dir)
+    }
+
+    /// The SMTP transport of the configured server.
+    fn mailer(&self) -> Result<SmtpTransport, EmailError> {
+        Ok(smtp_transport!(self, SmtpTransport))
+    }
+
+    /// The secret to log in to the SMTP server with.
+    ///
+    /// The fresh output of the `oauth2_token_command` with the `xoauth2`
+    /// mechanism, otherwise of the `password_command`,
+    /// otherwise the `password`.
+    ///
+    /// ## Errors
+    ///
+    /// - If the command fails, times out or prints nothing.
+    fn smtp_secret(&self) -> Result<String, EmailError> {
+        let xoauth2 = self.smtp_auth_mechanism == Some(SmtpAuthMechanism::Xoauth2);
+        match (&self.oauth2_token_command, &self.password_command) {
+            (Some(command), _) if xoauth2 => secret_from_command("oauth2_token_command", command)
+                .map_err(EmailError::OAuth2TokenError),
+            (_, Some(command)) => secret_from_command("password_command", command)
+                .map_err(EmailError::PasswordCommandError),
+            _ => Ok(self.password.clone()),
+        }
+    }
+
+    /// Tell a rejected OAuth2 token apart from other SMTP errors.
+    fn smtp_error(&self, error: smtp::Error) -> EmailError {
+        let rejected =
+            matches!(error.status(), Some(code) if code.to_string() == AUTHENTICATION_FAILED);
+        if rejected && self.smtp_auth_mechanism == Some(SmtpAuthMechanism::Xoauth2) {
+            EmailError::OAuth2TokenRejected(error)
+        } else {
+            error.into()
+        }
+    }
+
+    /// The delays between the retries of a failed send.
+    ///
+    /// See [`retry_delays`].
+    fn retry_delays(&self) -> impl Iterator<Item = Duration> {
+        retry_delays(
+            self.smtp_max_retries,
+            RETRY_BASE_DELAY,
+            RETRY_MAX_TOTAL_DELAY,
+        )
+    }
+
+    /// The TLS settings of the SMTP connection, according to the
+    /// [`TlsMode`].
+    fn tls(&self) -> Result<Tls, EmailError> {
+        let parameters = || TlsParameters::new_rustls(self.smtp_server.clone());
+        Ok(match self.tls_mode {
+            TlsMode::Wrapper => Tls::Wrapper(parameters()?),
+            TlsMode::StartTls => Tls::Required(parameters()?),
+            TlsMode::Opportunistic => Tls::Opportunistic(parameters()?),
+            TlsMode::None => Tls::None,
+        })
+    }
+
+    /// Preview the email without sending it.
+    ///
+    /// The email is fully built, so any error that would prevent it from
+    /// being sent, e.g. an invalid address or an unreadable attachment,
+    /// is returned here as well.
+    ///
+    /// ## Errors
+    ///
+    /// - If the email cannot be created.
+    /// - If the attachment cannot be read.
+    pub fn preview_email(&self, email_type: Email) -> Result<EmailPreview, EmailError> {
+        let email = self.create_email(email_type)?;
+        let headers = email.headers();
+
+        let subject = headers
+            .get::<Subject>()
+            .map(|subject| subject.as_ref().to_string())
+            .unwrap_or_default();
+        let to = header_mailboxes::<To>(headers);
+        let cc = header_mailboxes::<Cc>(headers);
+        // The `Bcc` header is stripped once the message is built,
+        // but the recipients are still in the envelope.
+        let bcc = email
+            .envelope()
+            .to()
+            .iter()
+            .filter(|address| {
+                !to.iter()
+                    .chain(cc.iter())
+                    .any(|mailbox| &mailbox.email == *address)
+            })
+            .map(ToString::to_string)
+            .collect();
+
+        let attachments = self.attachments_of(email_type);
+        let attachment_command = match email_type {
+            Email::DeadMan => self.attachment_command.as_ref(),
+            _ => None,
+        };
+        let mut attachments = if self.zips_attachments(attachments) {
+            vec![ZIP_FILENAME.to_string()]
+        } else {
+            attachments
+                .iter()
+                .map(|attachment| attachment_filename(attachment))
+                .collect::<Result<_, _>>()?
+        };
+        attachments.extend(attachment_command.map(|_| self.attachment_command_filename.clone()));
+
+        Ok(EmailPreview {
+            subject,
+            to: to.iter().map(ToString::to_string).collect(),
+            cc: cc.iter().map(ToString::to_string).collect(),
+            bcc,
+            body: self.body(email_type),
+            attachments,
+        })
+    }
+
+    /// The subject of the email.
+    pub(crate) fn subject(&self, email_type: Email) -> String {
+        let subject = match email_type {
+            Email::Warning => &self.subject_warning,
+            Email::Escalation(stage) => self
+                .escalations
+                .get(stage)
+                .map_or("", |escalation| &escalation.subject),
+            Email::DeadMan | Email::Personal(_) => &self.subject,
+            Email::Test => &self.subject_test,
+            Email::FinalGrace => &self.subject_final_grace,
+            Email::CheckInConfirmation => &self.subject_check_in,
+            Email::Reminder => &self.subject_reminder,
+        };
+        self.render(subject, email_type)
+    }
+
+    /// The plain text body of the email.
+    pub(crate) fn body(&self, email_type: Email) -> String {
+        let body = match email_type {
+            Email::Warning => &self.message_warning,
+            Email::Escalation(stage) => self
+                .escalations
+                .get(stage)
+                .map_or("", |escalation| &escalation.message),
+            Email::DeadMan => &self.message,
+            Email::Personal(index) => self
+                .personal_emails
+                .get(index)
+                .and_then(|personal| personal.message.as_deref())
+                .unwrap_or(&self.message),
+            Email::Test => &self.message_test,
+            Email::FinalGrace => &self.message_final_grace,
+            Email::CheckInConfirmation => &self.message_check_in,
+            Email::Reminder => &self.message_reminder,
+        };
+        let body = self.render(body, email_type);
+        match self.acknowledge_url(email_type) {
+            Some(url) => format!("{body}\n\n{}{url}", self.language.messages().acknowledge),
+            None => body,
+        }
+    }
+
+    /// The HTML alternative of the body of the email, if any.
+    pub(crate) fn body_html(&self, email_type: Email) -> Option<String> {
+        let html = match email_type {
+            Email::Warning => self.message_warning_html.as_deref(),
+            Email::DeadMan => self.message_html.as_deref(),
+            // A personal message has no HTML version
+            Email::Personal(index) => match self.personal_emails.get(index) {
+                Some(personal) if personal.message.is_some() => None,
+                _ => self.message_html.as_deref(),
+            },
+            _ => None,
+        };
+        html.map(|html| {
+            let html = self.render(html, email_type);
+            match self.acknowledge_url(email_type) {
+                Some(url) => format!(
+                    "{html}<p>{}<a href=\"{url}\">{url}</a></p>",
+                    self.language.messages().acknowledge
+                ),
+                None => html,
+            }
+        })
+    }
+
+    /// The acknowledgment link of the email, while the dead man's emails
+    /// are resent.
+    fn acknowledge_url(&self, email_type: Email) -> Option<&str> {
+        match email_type {
+            Email::DeadMan | Email::Personal(_) => self.acknowledge_url.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Expand the placeholders of a subject or message.
+    ///
+    /// - `{{date}}`: the current date and time, in RFC 3339.
+    /// - `{{timer_warning}}`: the warning timer.
+    /// - `{{elapsed}}`: the time since the last check-in when the email is
+    ///   sent, as scheduled by the timers.
+    ///
+    /// Unknown placeholders are left as they are.
+    fn render(&self, template: &str, email_type: Email) -> String {
+        let duration = |seconds: u64| {
+            let seconds = i64::try_from(seconds).unwrap_or(i64::MAX);
+            format_duration_exact(ChronoDuration::seconds(seconds), self.language)
+        };
+        template
+            .replace("{{date}}", &Local::now().to_rfc3339())
+            .replace("{{timer_warning}}", &duration(self.timer_warning))
+            .replace("{{elapsed}}", &duration(self.elapsed(email_type)))
+    }
+
+    /// Seconds since the last check-in when the email is sent,
+    /// as scheduled by the timers.
+    fn elapsed(&self, email_type: Email) -> u64 {
+        let escalations = |count: usize| -> u64 {
+            self.escalations
+                .iter()
+                .take(count)
+                .map(|escalation| escalation.timer)
+                .sum()
+        };
+        match email_type {
+            Email::Warning => self.timer_warning,
+            Email::Escalation(stage) => self.timer_warning + escalations(stage + 1),
+            Email::DeadMan | Email::Personal(_) => {
+                self.timer_warning + escalations(self.escalations.len()) + self.timer_dead_man
+            }
+            Email::Reminder => self
+                .timer_warning
+                .saturating_sub(self.reminder_before_secs.unwrap_or_default()),
+            Email::Test | Email::FinalGrace | Email::CheckInConfirmation => 0,
+        }
+    }
+
+    /// The parts of the `inline_images` referenced by `cid:` in the `html`.
+    ///
+    /// ## Errors
+    ///
+    /// - If an image cannot be read or is too large.
+    fn inline_image_parts(&self, html: &str) -> Result<Vec<SinglePart>, EmailError> {
+        self.inline_images
+            .iter()
+            .flatten()
+            .filter(|(cid, _)| html.contains(&format!("cid:{cid}")))
+            .map(|(cid, path)| {
+                let (body, content_type) = read_attachment(path, self.max_attachment_bytes)?;
+                Ok(Attachment::new_inline(cid.clone()).body(body, content_type))
+            })
+            .collect()
+    }
+
+    /// The `custom_headers`, checked to be valid.
+    ///
+    /// ## Errors
+    ///
+    /// - If a header name isn't printable ASCII without `:`,
+    ///   or a value spans several lines.
+    pub(crate) fn custom_headers(&self) -> Result<Vec<HeaderValue>, EmailError> {
+        self.custom_headers
+            .iter()
+            .flatten()
+            .map(|(name, value)| {
+                let invalid = || EmailError::InvalidHeader(name.clone());
+                let legal = !name.is_empty()
+                    && name
+                        .bytes()
+                        .all(|byte| byte.is_ascii_graphic() && byte != b':');
+                if !legal || value.contains(['\r', '\n']) {
+                    return Err(invalid());
+                }
+                let name = HeaderName::new_from_ascii(name.clone()).map_err(|_| invalid())?;
+                Ok(HeaderValue::new(name, value.clone()))
+            })
+            .collect()
+    }
+
+    /// Parse a comma-separated list of recipients.
+    ///
+    /// `@group:<name>` entries are expanded from the `recipients_file`.
+    ///
+    /// ## Errors
+    ///
+    /// - If an address is invalid, naming the address.
+    /// - If the recipients file cannot be read or parsed.
+    /// - If a group is not in the recipients file.
+    pub(crate) fn recipients(&self, list: &str) -> Result<Vec<Mailbox>, EmailError> {
+        let mut address_book = None;
+        let mut recipients = vec![];
+
+        for entry in list.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            match entry.strip_prefix(GROUP_PREFIX) {
+                Some(group) => {
+                    if address_book.is_none() {
+                        address_book = Some(self.address_book()?);
+                    }
+                    let members = address_book
+                        .as_ref()
+                        .and_then(|book| book.get(group))
+                        .ok_or_else(|| EmailError::UnknownGroup(group.to_string()))?;
+                    for member in members {
+                        recipients.push(parse_recipient(member)?);
+                    }
+                }
+                None => recipients.push(parse_recipient(entry)?),
+            }
+        }
+
+        Ok(recipients)
+    }
+
+    /// Load the recipient groups from the `recipients_file`.
+    ///
+    /// Without a recipients file there are no groups.
+    fn address_book(&self) -> Result<HashMap<String, Vec<String>>, EmailError> {
+        match &self.recipients_file {
+            Some(path) => Ok(toml::from_str(&fs::read_to_string(path)?)?),
+            None => Ok(HashMap::new()),
+        }
+    }
+
+    /// The attachments of the email.
+    ///
+    /// A personal email has its own attachments instead of the dead man's.
+    fn attachments_of(&self, email_type: Email) -> &[PathBuf] {
+        match email_type {
+            Email::DeadMan => &self.attachments,
+            Email::Personal(index) => self
+                .personal_emails
+                .get(index)
+                .map_or(&[], |personal| &personal.attachments),
+            _ => &[],
+        }
+    }
+
+    /// Whether the attachments are sent as a single zip.
+    fn zips_attachments(&self, attachments: &[PathBuf]) -> bool {
+        self.attachment_as_zip && attachments.len() > 1
+    }
+
+    /// Run the `attachment_command` and attach its output.
+    ///
+    /// ## Errors
+    ///
+    /// - If the command cannot be run, fails or times out.
+    /// - If the output is empty or larger than the `max_attachment_bytes`.
+    fn command_attachment_part(&self, command: &str) -> Result<SinglePart, EmailError> {
+        let timeout = Duration::from_secs(self.command_timeout_seconds);
+        let output = run_command_raw(command, &[], timeout)
+            .map_err(|error| EmailError::AttachmentCommandError(error.to_string()))?;
+        if !output.status.success() {
+            return Err(EmailError::AttachmentCommandError(format!(
+                "`attachment_command` exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        if output.stdout.is_empty() {
+            return Err(EmailError::AttachmentCommandError(
+                "`attachment_command` printed nothing".to_string(),
+            ));
+        }
+        let size = output.stdout.len() as u64;
+        if size > self.max_attachment_bytes {
+            return Err(EmailError::AttachmentTooLarge(
+                self.attachment_command_filename.clone(),
+                size,
+                self.max_attachment_bytes,
+            ));
+        }
+        let content_type = match &self.attachment_command_content_type {
+            Some(content_type) => ContentType::parse(content_type)?,
+            None => ContentType::parse(
+                mime_guess::from_path(&self.attachment_command_filename)
+                    .first_or_octet_stream()
+                    .as_ref(),
+            )?,
+        };
+
+        Ok(Attachment::new(self.attachment_command_filename.clone())
+            .body(output.stdout, content_type))
+    }
+
     /// Create the email to send.
     ///
     /// If an attachment is provided, the email will be created with the attachment.
     fn create_email(&self, email_type: Email) -> Result<Message, EmailError> {
         // Guaranteed config values
         let from = Mailbox::new(None, self.from.parse()?);
-        // Adjust the email to based on the email type
+        // Adjust the email to based on the email type,
+        // only the dead man's email, and the warning if asked to,
+        // goes to the recipients.
+        // Escalations go to their own recipients
+        let owner = Mailbox::new(None, Address::from_str(&self.from)?);
         let to = match email_type {
-            Email::Warning => Address::from_str(&self.from)?,
-            Email::DeadMan => Address::from_str(&self.to)?,
+            Email::DeadMan => self.recipients(&self.to)?,
+            Email::Personal(index) => match self.personal_emails.get(index) {
+                Some(personal) => self.recipients(&personal.to)?,
+                None => vec![],
+            },
+            Email::Warning if self.warning_notify_recipients => {
+                let mut to = vec![owner];
+                to.extend(self.recipients(&self.to)?);
+                to
+            }
+            Email::Escalation(stage) => match self
+                .escalations
+                .get(stage)
+                .and_then(|escalation| escalation.to.as_deref())
+            {
+                Some(to) => self.recipients(to)?,
+                None => vec![owner],
+            },
+            _ => vec![owner],
         };
-        let to = Mailbox::new(None, to);
 
         // Adjust the email builder based on the email type
-        let email_builder = Message::builder().from(from).to(to);
+        let email_builder = to
+            .into_iter()
+            .fold(Message::builder().from(from.clone()), |builder, to| {
+                builder.to(to)
+            });
+        let email_builder = email_builder.subject(self.subject(email_type));
+        let email_builder = self
+            .custom_headers()?
+            .into_iter()
+            .fold(email_builder, |builder, header| builder.raw_header(header));
+
+        // Copy the dead man's email to the cc and bcc recipients
         let email_builder = match email_type {
-            Email::Warning => email_builder.subject(&self.subject_warning),
-            Email::DeadMan => email_builder.subject(&self.subject),
+            Email::DeadMan => {
+                let cc = self.recipients(self.cc.as_deref().unwrap_or_default())?;
+                let bcc = self.recipients(self.bcc.as_deref().unwrap_or_default())?;
+                let email_builder = cc
+                    .into_iter()
+                    .fold(email_builder, |builder, cc| builder.cc(cc));
+                bcc.into_iter()
+                    .fold(email_builder, |builder, bcc| builder.bcc(bcc))
+            }
+            _ => email_builder,
+        };
+
+        // Send the replies to the dead man's email to someone else than `from`
+        let email_builder = match (email_type, &self.reply_to) {
+            (Email::DeadMan | Email::Personal(_), Some(reply_to)) => {
+                email_builder.reply_to(reply_to.parse()?)
+            }
+            _ => email_builder,
+        };
+
+        // Keep a copy of the dead man's email, hidden from the recipients
+        let email_builder = match email_type {
+            Email::DeadMan if self.copy_self_on_deadman => email_builder.bcc(from),
+            _ => email_builder,
         };
 
         // Prepare the email body
-        let text_part =
-            SinglePart::builder()
-                .header(ContentType::TEXT_PLAIN)
-                .body(match email_type {
-                    Email::Warning => self.message_warning.clone(),
-                    Email::DeadMan => self.message.clone(),
-                });
-
-        // Conditionally add the attachment for DeadMan email type
-        if let Email::DeadMan = email_type {
-            if let Some(attachment) = &self.attachment {
-                let filename = attachment
-                    .file_name()
-                    .ok_or_else(|| IoError::new(IoErrorKind::NotFound, "Failed to get filename"))?
-                    .to_string_lossy();
-                let filebody = fs::read(attachment)?;
-                let content_type = ContentType::parse(
-                    mime_guess::from_path(attachment)
-                        .first_or_octet_stream()
-                        .as_ref(),
-                )?;
-
-                // Create the attachment part
-                let attachment_part =
-                    Attachment::new(filename.to_string()).body(filebody, content_type);
-
-                // Construct and return the email with the attachment
-                let email = email_builder.multipart(
-                    MultiPart::mixed()
-                        .singlepart(text_part)
-                        .singlepart(attachment_part),
-                )?;
-                return Ok(email);
-            }
-        }
-
-        // For Warning email type or DeadMan without an attachment
-        let email = email_builder.singlepart(text_part)?;
+        let text_part = SinglePart::builder()
+            .header(ContentType::TEXT_PLAIN)
+            .body(self.body(email_type));
+
+        // With an HTML body, the plain text becomes its alternative,
+        // and the HTML is related to the inline images it references
+        let body = match self.body_html(email_type) {
+            Some(html) => {
+                let images = self.inline_image_parts(&html)?;
+                let html_part = SinglePart::builder()
+                    .header(ContentType::TEXT_HTML)
+                    .body(html);
+                let alternative = MultiPart::alternative().singlepart(text_part.clone());
+                Some(if images.is_empty() {
+                    alternative.singlepart(html_part)
+                } else {
+                    let related = images.into_iter().fold(
+                        MultiPart::related().singlepart(html_part),
+                        |related, image| related.singlepart(image),
+                    );
+                    alternative.multipart(related)
+                })
+            }
+            None => None,
+        };
+
+        // Only the emails with the secrets are encrypted
+        let pgp_key = match email_type {
+            Email::Warning | Email::DeadMan | Email::Personal(_) => {
+                self.pgp_public_key_path.as_deref()
+            }
+            _ => None,
+        };
+
+        // Conditionally add the attachments for the dead man's emails
+        let attachments = self.attachments_of(email_type);
+        let attachment_command = match email_type {
+            Email::DeadMan => self.attachment_command.as_ref(),
+            _ => None,
+        };
+        if !attachments.is_empty() || attachment_command.is_some() {
+            let mixed = match body {
+                Some(body) => MultiPart::mixed().multipart(body),
+                None => MultiPart::mixed().singlepart(text_part),
+            };
+            let multipart = if self.zips_attachments(attachments) {
+                mixed.singlepart(zip_part(attachments, self.max_attachment_bytes)?)
+            } else {
+                attachments.iter().try_fold(
+                    mixed,
+                    |multipart, attachment| -> Result<MultiPart, EmailError> {
+                        Ok(multipart
+                            .singlepart(attachment_part(attachment, self.max_attachment_bytes)?))
+                    },
+                )?
+            };
+            let multipart = match attachment_command {
+                Some(command) => multipart.singlepart(self.command_attachment_part(command)?),
+                None => multipart,
+            };
+
+            // Construct and return the email with the attachments
+            let email = match pgp_key {
+                Some(key) => {
+                    email_builder.multipart(encrypted_part(key, &multipart.formatted())?)?
+                }
+                None => email_builder.multipart(multipart)?,
+            };
+            return Ok(email);
+        }
+
+        // For the other email types or the dead man's emails without attachments
+        let email = match (body, pgp_key) {
+            (Some(body), Some(key)) => {
+                email_builder.multipart(encrypted_part(key, &body.formatted())?)?
+            }
+            (Some(body), None) => email_builder.multipart(body)?,
+            (None, Some(key)) => {
+                email_builder.multipart(encrypted_part(key, &text_part.formatted())?)?
+            }
+            (None, None) => email_builder.singlepart(text_part)?,
+        };
         Ok(email)
     }
 }
 
+impl From<SmtpAuthMechanism> for Mechanism {
+    fn from(mechanism: SmtpAuthMechanism) -> Self {
+        match mechanism {
+            SmtpAuthMechanism::Plain => Mechanism::Plain,
+            SmtpAuthMechanism::Login => Mechanism::Login,
+            SmtpAuthMechanism::Xoauth2 => Mechanism::Xoauth2,
+        }
+    }
+}
+
+/// The exponential backoff delays between at most `max_retries` retries.
+///
+/// The delays start at `base` and double every time,
+/// stopping early rather than exceeding a total of `max_total`.
+fn retry_delays(
+    max_retries: u32,
+    base: Duration,
+    max_total: Duration,
+) -> impl Iterator<Item = Duration> {
+    let mut total = Duration::ZERO;
+    (0..max_retries)
+        .map(move |retry| base.saturating_mul(2u32.saturating_pow(retry)))
+        .take_while(move |delay| {
+            total = total.saturating_add(*delay);
+            total <= max_total
+        })
+}
+
+/// Run the command configured as `field` and get the secret it prints,
+/// without the trailing newline.
+///
+/// ## Errors
+///
+/// - If the command fails, times out or prints nothing, describing why.
+fn secret_from_command(field: &str, command: &str) -> Result<String, String> {
+    let output =
+        run_command(command, &[], SECRET_COMMAND_TIMEOUT).map_err(|error| error.to_string())?;
+    if !output.status.success() {
+        return Err(format!(
+            "`{field}` exited with {}: {}",
+            output.status,
+            output.stderr.trim()
+        ));
+    }
+    let secret = output.stdout.trim_end_matches(&['\r', '\n'][..]);
+    if secret.is_empty() {
+        return Err(format!("`{field}` printed nothing"));
+    }
+    Ok(secret.to_string())
+}
+
+/// Wrap the formatted MIME `part` in a PGP/MIME encrypted part (RFC 3156),
+/// encrypted to the OpenPGP public key at `key`.
+fn encrypted_part(key: &Path, part: &[u8]) -> Result<MultiPart, EmailError> {
+    let encrypted = pgp_encrypt(key, part)
+        .map_err(|error| EmailError::EncryptionError(key.display().to_string(), error))?;
+    Ok(
+        MultiPart::encrypted("application/pgp-encrypted".to_string())
+            .singlepart(
+                SinglePart::builder()
+                    .header(ContentType::parse("application/pgp-encrypted")?)
+                    .body(String::from("Version: 1\r\n")),
+            )
+            .singlepart(
+                SinglePart::builder()
+                    .header(ContentType::parse(
+                        "application/octet-stream; name=\"encrypted.asc\"",
+                    )?)
+                    .body(encrypted),
+            ),
+    )
+}
+
+/// Encrypt `data` to the OpenPGP public key at `key` with `gpg`,
+/// ASCII-armored.
+///
+/// `gpg` runs in a throwaway home directory, so the user's keyring
+/// is neither needed nor touched. The directory is freshly created with
+/// a random name, readable only by the user, so it can't be planted.
+fn pgp_encrypt(key: &Path, data: &[u8]) -> Result<String, String> {
+    let home = tempfile::Builder::new()
+        .prefix("dead-man-switch-gpg-")
+        .tempdir()
+        .map_err(|error| error.to_string())?;
+    let output = gpg_encrypt(home.path(), key, data);
+    let _ = home.close();
+    let output = output.map_err(|error| format!("cannot run gpg: {error}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "gpg exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    String::from_utf8(output.stdout).map_err(|error| error.to_string())
+}
+
+/// Run `gpg` with `home` as its home directory to encrypt `data` to `key`.
+fn gpg_encrypt(home: &Path, key: &Path, data: &[u8]) -> Result<Output, IoError> {
+    let mut child = Command::new("gpg")
+        .arg("--homedir")
+        .arg(home)
+        .args(["--batch", "--quiet", "--no-tty", "--armor"])
+        .args(["--trust-model", "always", "--encrypt", "--recipient-file"])
+        .arg(key)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // Write from another thread, gpg may fill its output before reading it all
+    let mut stdin = child.stdin.take().expect("the stdin is piped");
+    let data = data.to_vec();
+    let writer = thread::spawn(move || stdin.write_all(&data));
+    let output = child.wait_with_output()?;
+    match writer.join() {
+        Ok(Err(error)) if output.status.success() => Err(error),
+        _ => Ok(output),
+    }
+}
+
+/// Append the previewed email to the dry run log at `path`.
+fn log_dry_run(path: &Path, preview: &EmailPreview) -> Result<(), IoError> {
+    crate::config::log_dry_run(path, &format!("not sent:\n{preview}\n"))
+}
+
+/// Parse a recipient, naming it if it's invalid.
+fn parse_recipient(recipient: &str) -> Result<Mailbox, EmailError> {
+    recipient
+        .parse()
+        .map_err(|error| EmailError::InvalidRecipient(recipient.to_string(), error))
+}
+
+/// Create the part of an attachment, with its MIME type guessed from
+/// its extension.
+///
+/// ## Errors
+///
+/// - If the attachment cannot be read, naming the path that failed.
+fn attachment_part(attachment: &Path, max_bytes: u64) -> Result<SinglePart, EmailError> {
+    let filename = attachment_filename(attachment)?;
+    let (filebody, content_type) = read_attachment(attachment, max_bytes)?;
+
+    Ok(Attachment::new(filename).body(filebody, content_type))
+}
+
+/// Read an attachment, with its MIME type guessed from its extension.
+///
+/// ## Errors
+///
+/// - If the attachment cannot be read, naming the path that failed.
+/// - If the attachment is larger than `max_bytes`.
+fn read_attachment(
+    attachment: &Path,
+    max_bytes: u64,
+) -> Result<(Vec<u8>, ContentType), EmailError> {
+    let read_error = |error: IoError| {
+        IoError::new(
+            error.kind(),
+            format!(
+                "Failed to read attachment {}: {error}",
+                attachment.display()
+            ),
+        )
+    };
+    // Check the size before loading a huge file into memory
+    let size = fs::metadata(attachment).map_err(read_error)?.len();
+    if size > max_bytes {
+        return Err(EmailError::AttachmentTooLarge(
+            attachment.display().to_string(),
+            size,
+            max_bytes,
+        ));
+    }
+    let filebody = fs::read(attachment).map_err(read_error)?;
+    let content_type = ContentType::parse(
+        mime_guess::from_path(attachment)
+            .first_or_octet_stream()
+            .as_ref(),
+    )?;
+
+    Ok((filebody, content_type))
+}
+
+/// Zip the attachments into a single `attachments.zip` part.
+///
+/// ## Errors
+///
+/// - If an attachment cannot be read, naming the path that failed.
+/// - If an attachment or the zip is larger than `max_bytes`.
+fn zip_part(attachments: &[PathBuf], max_bytes: u64) -> Result<SinglePart, EmailError> {
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    for attachment in attachments {
+        let (filebody, _) = read_attachment(attachment, max_bytes)?;
+        zip.start_file(attachment_filename(attachment)?, options)?;
+        zip.write_all(&filebody)?;
+    }
+    let zipped = zip.finish()?.into_inner();
+
+    let size = zipped.len() as u64;
+    if size > max_bytes {
+        return Err(EmailError::AttachmentTooLarge(
+            ZIP_FILENAME.to_string(),
+            size,
+            max_bytes,
+        ));
+    }
+    Ok(Attachment::new(ZIP_FILENAME.to_string())
+        .body(zipped, ContentType::parse("application/zip")?))
+}
+
+/// Get the filename of an attachment.
+fn attachment_filename(attachment: &Path) -> Result<String, IoError> {
+    attachment
+        .file_name()
+        .map(|filename| filename.to_string_lossy().to_string())
+        .ok_or_else(|| IoError::new(IoErrorKind::NotFound, "Failed to get filename"))
+}
+
+/// Get the mailboxes of an address header, e.g. `To` or `Cc`.
+fn header_mailboxes<H>(headers: &Headers) -> Vec<Mailbox>
+where
+    H: Header + Into<Mailboxes>,
+{
+    headers
+        .get::<H>()
+        .map(|header| header.into().into_iter().collect())
+        .unwrap_or_default()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::PathBuf;
+    use crate::config::{Escalation, PersonalEmail};
+    use std::collections::BTreeMap;
 
     fn get_test_config() -> Config {
         Config {
@@ -151,9 +1226,10 @@ mod tests {
             subject_warning: "Test Warning Subject".to_string(),
             to: "recipient@example.com".to_string(),
             from: "sender@example.com".to_string(),
-            attachment: None,
+            attachments: vec![],
             timer_warning: 60,
             timer_dead_man: 120,
+            ..Config::default()
         }
     }
 
@@ -170,10 +1246,726 @@ mod tests {
     fn test_create_email_with_attachment() {
         let mut config = get_test_config();
         // Assuming there's a test file at this path
-        config.attachment = Some(PathBuf::from("README.md"));
+        config.attachments = vec![PathBuf::from("README.md")];
         let email_result = config.create_email(Email::Warning);
         assert!(email_result.is_ok());
         let email_result = config.create_email(Email::DeadMan);
         assert!(email_result.is_ok());
     }
+
+    #[test]
+    fn test_create_email_with_multiple_attachments() {
+        let mut config = get_test_config();
+        config.attachments = vec![PathBuf::from("README.md"), PathBuf::from("Cargo.toml")];
+        let preview = config.preview_email(Email::DeadMan).unwrap();
+        assert_eq!(preview.attachments, vec!["README.md", "Cargo.toml"]);
+
+        let email =
+            String::from_utf8(config.create_email(Email::DeadMan).unwrap().formatted()).unwrap();
+        assert!(email.contains("filename=\"README.md\""));
+        assert!(email.contains("filename=\"Cargo.toml\""));
+    }
+
+    #[test]
+    fn test_zipped_attachments() {
+        let mut config = get_test_config();
+        config.attachment_as_zip = true;
+
+        // A single attachment isn't zipped
+        config.attachments = vec![PathBuf::from("README.md")];
+        let preview = config.preview_email(Email::DeadMan).unwrap();
+        assert_eq!(preview.attachments, vec!["README.md"]);
+
+        config.attachments = vec![PathBuf::from("README.md"), PathBuf::from("Cargo.toml")];
+        let preview = config.preview_email(Email::DeadMan).unwrap();
+        assert_eq!(preview.attachments, vec!["attachments.zip"]);
+        let email =
+            String::from_utf8(config.create_email(Email::DeadMan).unwrap().formatted()).unwrap();
+        assert!(email.contains("Content-Type: application/zip"));
+        assert!(email.contains("filename=\"attachments.zip\""));
+        assert!(!email.contains("filename=\"README.md\""));
+
+        config.attachments = vec![PathBuf::from("README.md"), PathBuf::from("missing.pdf")];
+        let error = config.create_email(Email::DeadMan).unwrap_err();
+        assert!(error.to_string().contains("missing.pdf"));
+    }
+
+    #[test]
+    fn test_missing_attachment_names_the_path() {
+        let mut config = get_test_config();
+        config.attachments = vec![PathBuf::from("README.md"), PathBuf::from("missing.pdf")];
+        let error = config.create_email(Email::DeadMan).unwrap_err();
+        assert!(matches!(error, EmailError::IoError(_)));
+        assert!(error.to_string().contains("missing.pdf"));
+    }
+
+    #[test]
+    fn test_attachment_too_large() {
+        let mut config = get_test_config();
+        config.attachments = vec![PathBuf::from("README.md")];
+        let size = fs::metadata("README.md").unwrap().len();
+
+        config.max_attachment_bytes = size;
+        assert!(config.create_email(Email::DeadMan).is_ok());
+        assert!(config.warnings().is_empty());
+
+        config.max_attachment_bytes = size - 1;
+        let error = config.create_email(Email::DeadMan).unwrap_err();
+        assert!(
+            matches!(&error, EmailError::AttachmentTooLarge(path, actual, max) if path == "README.md" && *actual == size && *max == size - 1)
+        );
+        assert_eq!(config.warnings().len(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_attachment_command() {
+        let mut config = get_test_config();
+        config.attachment_command = Some("printf 'fresh export'".to_string());
+        config.attachment_command_filename = "export.json".to_string();
+        let preview = config.preview_email(Email::DeadMan).unwrap();
+        assert_eq!(preview.attachments, vec!["export.json"]);
+
+        let email =
+            String::from_utf8(config.create_email(Email::DeadMan).unwrap().formatted()).unwrap();
+        assert!(email.contains("filename=\"export.json\""));
+        assert!(email.contains("Content-Type: application/json"));
+        assert!(email.contains("fresh export"));
+
+        config.attachment_command_content_type = Some("text/plain".to_string());
+        let email =
+            String::from_utf8(config.create_email(Email::DeadMan).unwrap().formatted()).unwrap();
+        assert!(email.contains("Content-Type: text/plain"));
+
+        // Only the dead man's email runs it
+        config.attachment_command = Some("exit 3".to_string());
+        assert!(config.create_email(Email::Warning).is_ok());
+        let error = config.create_email(Email::DeadMan).unwrap_err();
+        assert!(matches!(error, EmailError::AttachmentCommandError(_)));
+        assert!(error.to_string().contains("exit status: 3"));
+
+        config.attachment_command = Some("true".to_string());
+        assert!(matches!(
+            config.create_email(Email::DeadMan),
+            Err(EmailError::AttachmentCommandError(_))
+        ));
+    }
+
+    #[test]
+    fn test_placeholders() {
+        let mut config = get_test_config();
+        config.timer_warning = 2 * 24 * 60 * 60;
+        config.timer_dead_man = 60 * 60;
+        config.subject = "Sent on {{date}}".to_string();
+        config.message =
+            "Gone for {{elapsed}}, warned after {{timer_warning}}. {{unknown}}".to_string();
+        config.subject_warning = "{{elapsed}}".to_string();
+
+        let preview = config.preview_email(Email::DeadMan).unwrap();
+        let date = preview.subject.strip_prefix("Sent on ").unwrap();
+        assert!(chrono::DateTime::parse_from_rfc3339(date).is_ok());
+        assert_eq!(
+            preview.body,
+            "Gone for 2 days, 1 hour, warned after 2 days. {{unknown}}"
+        );
+        assert_eq!(config.subject(Email::Warning), "2 days");
+    }
+
+    #[test]
+    fn test_html_body() {
+        let mut config = get_test_config();
+        let email =
+            String::from_utf8(config.create_email(Email::DeadMan).unwrap().formatted()).unwrap();
+        assert!(!email.contains("multipart"));
+
+        config.message_html = Some("<p>This is a <b>test</b> message</p>".to_string());
+        let email =
+            String::from_utf8(config.create_email(Email::DeadMan).unwrap().formatted()).unwrap();
+        assert!(email.contains("multipart/alternative"));
+        assert!(email.contains("text/plain"));
+        assert!(email.contains("text/html"));
+        assert!(!email.contains("multipart/mixed"));
+
+        // Only the warning has an HTML alternative too
+        let email =
+            String::from_utf8(config.create_email(Email::Warning).unwrap().formatted()).unwrap();
+        assert!(!email.contains("text/html"));
+    }
+
+    #[test]
+    fn test_html_body_with_attachment() {
+        let mut config = get_test_config();
+        config.message_html = Some("<p>This is a test message</p>".to_string());
+        config.attachments = vec![PathBuf::from("README.md")];
+        let email =
+            String::from_utf8(config.create_email(Email::DeadMan).unwrap().formatted()).unwrap();
+
+        // The alternative is nested in the mixed multipart, before the attachment
+        let mixed = email.find("multipart/mixed").unwrap();
+        let alternative = email.find("multipart/alternative").unwrap();
+        let attachment = email.find("filename=\"README.md\"").unwrap();
+        assert!(mixed < alternative && alternative < attachment);
+    }
+
+    #[test]
+    fn test_preview_email() {
+        let mut config = get_test_config();
+        config.attachments = vec![PathBuf::from("README.md")];
+
+        let preview = config.preview_email(Email::Warning).unwrap();
+        assert_eq!(preview.subject, "Test Warning Subject");
+        assert_eq!(preview.to, vec!["sender@example.com"]);
+        assert!(preview.cc.is_empty());
+        assert!(preview.bcc.is_empty());
+        assert_eq!(preview.body, "This is a test warning message");
+        assert!(preview.attachments.is_empty());
+
+        let preview = config.preview_email(Email::DeadMan).unwrap();
+        assert_eq!(preview.subject, "Test Subject");
+        assert_eq!(preview.to, vec!["recipient@example.com"]);
+        assert_eq!(preview.body, "This is a test message");
+        assert_eq!(preview.attachments, vec!["README.md"]);
+    }
+
+    #[test]
+    fn test_multiple_recipients() {
+        let mut config = get_test_config();
+        config.to = "a@example.com, Bob <b@example.com>,".to_string();
+        let preview = config.preview_email(Email::DeadMan).unwrap();
+        assert_eq!(preview.to, vec!["a@example.com", "Bob <b@example.com>"]);
+    }
+
+    #[test]
+    fn test_retry_delays() {
+        let second = Duration::from_secs(1);
+        let delays: Vec<_> = retry_delays(3, second, Duration::from_secs(60)).collect();
+        assert_eq!(delays, vec![second, second * 2, second * 4]);
+
+        // The total delay is capped
+        let delays: Vec<_> = retry_delays(10, second, Duration::from_secs(5)).collect();
+        assert_eq!(delays, vec![second, second * 2]);
+
+        assert_eq!(retry_delays(0, second, Duration::from_secs(60)).count(), 0);
+    }
+
+    #[test]
+    fn test_tls() {
+        let mut config = get_test_config();
+        assert!(matches!(config.tls().unwrap(), Tls::Required(_)));
+
+        config.tls_mode = TlsMode::Wrapper;
+        assert!(matches!(config.tls().unwrap(), Tls::Wrapper(_)));
+        config.tls_mode = TlsMode::Opportunistic;
+        assert!(matches!(config.tls().unwrap(), Tls::Opportunistic(_)));
+        config.tls_mode = TlsMode::None;
+        assert!(matches!(config.tls().unwrap(), Tls::None));
+    }
+
+    #[test]
+    fn test_cc_and_bcc() {
+        let mut config = get_test_config();
+        config.cc = Some("lawyer@example.com".to_string());
+        config.bcc = Some("sister@example.com, brother@example.com".to_string());
+
+        let preview = config.preview_email(Email::DeadMan).unwrap();
+        assert_eq!(preview.to, vec!["recipient@example.com"]);
+        assert_eq!(preview.cc, vec!["lawyer@example.com"]);
+        assert_eq!(
+            preview.bcc,
+            vec!["sister@example.com", "brother@example.com"]
+        );
+
+        // Only the dead man's email is copied
+        let preview = config.preview_email(Email::Warning).unwrap();
+        assert!(preview.cc.is_empty());
+        assert!(preview.bcc.is_empty());
+
+        // Blank lists are the same as no list
+        config.cc = Some("  ".to_string());
+        config.bcc = Some("".to_string());
+        let preview = config.preview_email(Email::DeadMan).unwrap();
+        assert!(preview.cc.is_empty());
+        assert!(preview.bcc.is_empty());
+    }
+
+    #[test]
+    fn test_reply_to() {
+        let mut config = get_test_config();
+        let email = config.create_email(Email::DeadMan).unwrap();
+        assert!(email.headers().get_raw("Reply-To").is_none());
+
+        config.reply_to = Some("spouse@example.com".to_string());
+        let email = config.create_email(Email::DeadMan).unwrap();
+        assert_eq!(
+            email.headers().get_raw("Reply-To"),
+            Some("spouse@example.com")
+        );
+        let email = config.create_email(Email::Warning).unwrap();
+        assert!(email.headers().get_raw("Reply-To").is_none());
+
+        config.reply_to = Some("".to_string());
+        let result = config.create_email(Email::DeadMan);
+        assert!(matches!(result, Err(EmailError::EmailError(_))));
+    }
+
+    #[test]
+    fn test_custom_headers() {
+        let mut config = get_test_config();
+        config.custom_headers = Some(
+            [
+                ("X-Priority".to_string(), "1".to_string()),
+                ("Auto-Submitted".to_string(), "auto-generated".to_string()),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        let email = config.create_email(Email::DeadMan).unwrap();
+        assert_eq!(email.headers().get_raw("X-Priority"), Some("1"));
+        assert_eq!(
+            email.headers().get_raw("Auto-Submitted"),
+            Some("auto-generated")
+        );
+
+        for (name, value) in [
+            ("X Priority", "1"),
+            ("X-Priority:", "1"),
+            ("", "1"),
+            ("X-Bcc", "a\r\nBcc: b"),
+        ] {
+            config.custom_headers = Some(
+                [(name.to_string(), value.to_string())]
+                    .into_iter()
+                    .collect(),
+            );
+            let result = config.create_email(Email::DeadMan);
+            assert!(matches!(result, Err(EmailError::InvalidHeader(header)) if header == name));
+        }
+    }
+
+    #[test]
+    fn test_invalid_recipient_is_named() {
+        let mut config = get_test_config();
+        config.cc = Some("lawyer@example.com, not-an-address".to_string());
+        let error = config.create_email(Email::DeadMan).unwrap_err();
+        assert!(
+            matches!(&error, EmailError::InvalidRecipient(recipient, _) if recipient == "not-an-address")
+        );
+        assert!(error.to_string().contains("not-an-address"));
+    }
+
+    #[test]
+    fn test_invalid_to_is_an_error() {
+        let mut config = get_test_config();
+        config.to = "valid@x.com, not-an-email".to_string();
+        let error = config.create_email(Email::DeadMan).unwrap_err();
+        assert!(
+            matches!(&error, EmailError::InvalidRecipient(recipient, _) if recipient == "not-an-email")
+        );
+    }
+
+    #[test]
+    fn test_inline_images() {
+        let dir = tempfile::tempdir().unwrap();
+        let logo = dir.path().join("logo.png");
+        fs::write(&logo, b"not really a png").unwrap();
+        let mut config = get_test_config();
+        config.message_html = Some("<img src=\"cid:logo\">".to_string());
+        config.inline_images = Some(BTreeMap::from([
+            ("logo".to_string(), logo),
+            ("unused".to_string(), dir.path().join("missing.png")),
+        ]));
+
+        let email =
+            String::from_utf8(config.create_email(Email::DeadMan).unwrap().formatted()).unwrap();
+        assert!(email.contains("multipart/related"));
+        assert!(email.contains("Content-ID: <logo>"));
+        assert!(email.contains("Content-Disposition: inline"));
+        assert!(!email.contains("unused"));
+
+        // The warning has no HTML referencing the logo
+        let email =
+            String::from_utf8(config.create_email(Email::Warning).unwrap().formatted()).unwrap();
+        assert!(!email.contains("Content-ID"));
+    }
+
+    #[test]
+    fn test_pgp_encryption_fails_closed() {
+        let dir = tempfile::tempdir().unwrap();
+
+        // Fails closed without a usable key
+        let mut config = get_test_config();
+        config.pgp_public_key_path = Some(dir.path().join("missing.asc"));
+        assert!(matches!(
+            config.create_email(Email::DeadMan),
+            Err(EmailError::EncryptionError(_, _))
+        ));
+        assert!(config.create_email(Email::Test).is_ok());
+    }
+
+    #[test]
+    #[ignore = "needs gpg"]
+    fn test_pgp_encryption() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        let home = dir.join("gnupg");
+        fs::create_dir_all(&home).unwrap();
+        let gpg = |args: &[&str]| {
+            Command::new("gpg")
+                .arg("--homedir")
+                .arg(&home)
+                .args(["--batch", "--quiet", "--passphrase", ""])
+                .args(args)
+                .output()
+        };
+
+        let generated = gpg(&[
+            "--quick-gen-key",
+            "Test <test@example.com>",
+            "default",
+            "default",
+            "never",
+        ])
+        .expect("gpg is installed");
+        assert!(generated.status.success());
+        let mut config = get_test_config();
+        let key = dir.join("key.asc");
+        let exported = gpg(&["--armor", "--export", "test@example.com"]).unwrap();
+        fs::write(&key, exported.stdout).unwrap();
+        config.pgp_public_key_path = Some(key);
+
+        let email =
+            String::from_utf8(config.create_email(Email::DeadMan).unwrap().formatted()).unwrap();
+        assert!(email.contains("multipart/encrypted"));
+        assert!(email.contains("-----BEGIN PGP MESSAGE-----"));
+        assert!(!email.contains(&config.message));
+
+        let encrypted = dir.join("email.asc");
+        let start = email.find("-----BEGIN PGP MESSAGE-----").unwrap();
+        let end = email.find("-----END PGP MESSAGE-----").unwrap();
+        fs::write(
+            &encrypted,
+            &email[start..end + "-----END PGP MESSAGE-----".len()],
+        )
+        .unwrap();
+        let decrypted = gpg(&["--decrypt", encrypted.to_str().unwrap()]).unwrap();
+        let decrypted = String::from_utf8(decrypted.stdout).unwrap();
+        assert!(decrypted.contains("Content-Type: text/plain"));
+        assert!(decrypted.contains(&config.message));
+
+        let _ = Command::new("gpgconf")
+            .arg("--homedir")
+            .arg(&home)
+            .args(["--kill", "all"])
+            .status();
+    }
+
+    #[test]
+    fn test_recipient_groups() {
+        let dir = tempfile::tempdir().unwrap();
+        let recipients_file = dir.path().join("recipients.toml");
+        fs::write(
+            &recipients_file,
+            "family = [\"mom@example.com\", \"dad@example.com\"]\n",
+        )
+        .unwrap();
+
+        let mut config = get_test_config();
+        config.recipients_file = Some(recipients_file);
+        config.to = "lawyer@example.com, @group:family".to_string();
+        let preview = config.preview_email(Email::DeadMan).unwrap();
+        assert_eq!(
+            preview.to,
+            vec!["lawyer@example.com", "mom@example.com", "dad@example.com"]
+        );
+
+        config.to = "@group:friends".to_string();
+        let result = config.create_email(Email::DeadMan);
+        assert!(matches!(result, Err(EmailError::UnknownGroup(group)) if group == "friends"));
+    }
+
+    #[test]
+    fn test_file_transport() {
+        // A missing directory is created on first use
+        let dir = tempfile::tempdir().unwrap();
+        let emails_dir = dir.path().join("emails");
+
+        let mut config = get_test_config();
+        config.transport = EmailTransport::File;
+        config.file_transport_dir = Some(emails_dir.clone());
+        config.check_smtp_connection().unwrap();
+        config.send_email(Email::DeadMan).unwrap();
+
+        let emails: Vec<_> = fs::read_dir(&emails_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| {
+                path.extension()
+                    .map_or(false, |extension| extension == "eml")
+            })
+            .collect();
+        assert_eq!(emails.len(), 1);
+        let email = fs::read_to_string(&emails[0]).unwrap();
+        assert!(email.contains(&format!("Subject: {}", config.subject)));
+
+        config.file_transport_dir = None;
+        assert!(config.send_email(Email::DeadMan).is_err());
+    }
+
+    #[test]
+    fn test_personal_emails() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut config = get_test_config();
+        config.transport = EmailTransport::File;
+        config.file_transport_dir = Some(dir.path().to_path_buf());
+        config.attachments = vec![PathBuf::from("Cargo.toml")];
+        config.personal_emails = vec![
+            PersonalEmail {
+                to: "lawyer@example.com".to_string(),
+                message: None,
+                attachments: vec![PathBuf::from("README.md")],
+            },
+            PersonalEmail {
+                to: "spouse@example.com".to_string(),
+                message: Some("A letter for you".to_string()),
+                attachments: vec![PathBuf::from("missing.pdf")],
+            },
+        ];
+
+        let preview = config.preview_email(Email::Personal(0)).unwrap();
+        assert_eq!(preview.to, vec!["lawyer@example.com"]);
+        assert_eq!(preview.subject, config.subject);
+        assert_eq!(preview.body, config.message);
+        assert_eq!(preview.attachments, vec!["README.md"]);
+        assert_eq!(config.body(Email::Personal(1)), "A letter for you");
+
+        // The failing personal email doesn't stop the others
+        let error = config.send_email(Email::DeadMan).unwrap_err();
+        assert!(matches!(
+            &error,
+            EmailError::PersonalEmailFailed(to, _) if to == "spouse@example.com"
+        ));
+        assert!(error.to_string().contains("missing.pdf"));
+        assert!(error.only_personal());
+        let emails: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| {
+                path.extension()
+                    .map_or(false, |extension| extension == "eml")
+            })
+            .map(|path| fs::read_to_string(path).unwrap())
+            .collect();
+        assert_eq!(emails.len(), 2);
+        assert!(emails
+            .iter()
+            .any(|email| email.contains("filename=\"Cargo.toml\"")));
+        assert!(emails
+            .iter()
+            .any(|email| email.contains("To: lawyer@example.com")
+                && email.contains("filename=\"README.md\"")
+                && !email.contains("Cargo.toml")));
+
+        // Only the dead man's email comes with the personal emails
+        config.send_email(Email::Warning).unwrap();
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 3);
+    }
+
+    #[test]
+    fn test_acknowledge_link() {
+        let mut config = get_test_config();
+        config.message_html = Some("<p>Goodbye</p>".to_string());
+        config.acknowledge_url = Some("https://switch.example.com/acknowledge/abc".to_string());
+
+        let body = config.body(Email::DeadMan);
+        assert!(body.starts_with(&config.message));
+        assert!(body.ends_with(": https://switch.example.com/acknowledge/abc"));
+        let html = config.body_html(Email::DeadMan).unwrap();
+        assert!(html.contains("<a href=\"https://switch.example.com/acknowledge/abc\">"));
+        assert!(!config.body(Email::Warning).contains("acknowledge"));
+    }
+
+    #[test]
+    fn test_log_dry_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dry_run.log");
+
+        let mut config = get_test_config();
+        config.cc = Some("lawyer@example.com".to_string());
+        let preview = config.preview_email(Email::DeadMan).unwrap();
+        log_dry_run(&path, &preview).unwrap();
+        log_dry_run(&path, &preview).unwrap();
+
+        let log = fs::read_to_string(&path).unwrap();
+        assert_eq!(log.matches("Dry run, not sent:").count(), 2);
+        assert!(log.contains(&format!("Subject: {}\n", config.subject)));
+        assert!(log.contains(&format!("To: {}\n", config.to)));
+        assert!(log.contains("Cc: lawyer@example.com\n"));
+        assert!(!log.contains("Bcc:"));
+        assert!(log.contains(&config.message));
+    }
+
+    #[test]
+    fn test_recipient_group_without_recipients_file() {
+        let mut config = get_test_config();
+        config.to = "@group:family".to_string();
+        let result = config.create_email(Email::DeadMan);
+        assert!(matches!(result, Err(EmailError::UnknownGroup(_))));
+    }
+
+    #[test]
+    fn test_test_email() {
+        let mut config = get_test_config();
+        config.attachments = vec![PathBuf::from("README.md")];
+        let preview = config.preview_email(Email::Test).unwrap();
+        assert_eq!(preview.subject, config.subject_test);
+        assert_eq!(preview.to, vec!["sender@example.com"]);
+        assert_eq!(preview.body, config.message_test);
+        assert!(preview.attachments.is_empty());
+    }
+
+    #[test]
+    fn test_send_test_email_unreachable() {
+        // Nothing listens on the discard port
+        let mut config = get_test_config();
+        config.smtp_server = "127.0.0.1".to_string();
+        config.smtp_port = 9;
+        config.smtp_max_retries = 0;
+        assert!(config.send_test_email().is_err());
+    }
+
+    #[test]
+    fn test_check_smtp_connection_unreachable() {
+        // Nothing listens on the discard port
+        let mut config = get_test_config();
+        config.smtp_server = "127.0.0.1".to_string();
+        config.smtp_port = 9;
+        assert!(config.check_smtp_connection().is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_oauth2_token_command() {
+        let mut config = get_test_config();
+        config.oauth2_token_command = Some("echo ya29.token".to_string());
+        // Only used with the XOAUTH2 mechanism
+        assert_eq!(config.smtp_secret().unwrap(), "password");
+
+        config.smtp_auth_mechanism = Some(SmtpAuthMechanism::Xoauth2);
+        assert_eq!(config.smtp_secret().unwrap(), "ya29.token");
+
+        config.oauth2_token_command = Some("echo expired >&2; exit 1".to_string());
+        let error = config.smtp_secret().unwrap_err();
+        assert!(matches!(error, EmailError::OAuth2TokenError(_)));
+        assert!(error.to_string().contains("expired"));
+
+        config.oauth2_token_command = Some("true".to_string());
+        assert!(matches!(
+            config.smtp_secret(),
+            Err(EmailError::OAuth2TokenError(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_password_command() {
+        let mut config = get_test_config();
+        config.password_command = Some("printf ' s3cret \\n'".to_string());
+        // Only the line ending is stripped, the password may have spaces
+        assert_eq!(config.smtp_secret().unwrap(), " s3cret ");
+
+        config.password_command = Some("exit 1".to_string());
+        assert!(matches!(
+            config.smtp_secret(),
+            Err(EmailError::PasswordCommandError(_))
+        ));
+    }
+
+    #[test]
+    fn test_escalation_email() {
+        let mut config = get_test_config();
+        config.escalations = vec![
+            Escalation {
+                timer: 60,
+                subject: "Urgent".to_string(),
+                message: "Check in now".to_string(),
+                to: None,
+            },
+            Escalation {
+                timer: 60,
+                subject: "Call me".to_string(),
+                message: "Please call".to_string(),
+                to: Some("friend@example.com, sister@example.com".to_string()),
+            },
+        ];
+        // Escalations are tables, so they must survive serialization
+        let toml = toml::to_string(&config).unwrap();
+        assert_eq!(toml::from_str::<Config>(&toml).unwrap(), config);
+
+        let preview = config.preview_email(Email::Escalation(0)).unwrap();
+        assert_eq!(preview.subject, "Urgent");
+        assert_eq!(preview.to, vec!["sender@example.com"]);
+        assert_eq!(preview.body, "Check in now");
+
+        let preview = config.preview_email(Email::Escalation(1)).unwrap();
+        assert_eq!(preview.subject, "Call me");
+        assert_eq!(preview.to, vec!["friend@example.com", "sister@example.com"]);
+    }
+
+    #[test]
+    fn test_final_grace_email() {
+        let mut config = get_test_config();
+        config.attachments = vec![PathBuf::from("README.md")];
+        let preview = config.preview_email(Email::FinalGrace).unwrap();
+        assert_eq!(preview.subject, config.subject_final_grace);
+        assert_eq!(preview.to, vec!["sender@example.com"]);
+        assert_eq!(preview.body, config.message_final_grace);
+        assert!(preview.attachments.is_empty());
+    }
+
+    #[test]
+    fn test_check_in_confirmation_email() {
+        let mut config = get_test_config();
+        config.attachments = vec![PathBuf::from("README.md")];
+        let preview = config.preview_email(Email::CheckInConfirmation).unwrap();
+        assert_eq!(preview.subject, config.subject_check_in);
+        assert_eq!(preview.to, vec!["sender@example.com"]);
+        assert_eq!(preview.body, config.message_check_in);
+        assert!(preview.attachments.is_empty());
+    }
+
+    #[test]
+    fn test_copy_self_on_deadman() {
+        let mut config = get_test_config();
+        let preview = config.preview_email(Email::DeadMan).unwrap();
+        assert!(preview.bcc.is_empty());
+
+        config.copy_self_on_deadman = true;
+        let preview = config.preview_email(Email::DeadMan).unwrap();
+        assert_eq!(preview.to, vec!["recipient@example.com"]);
+        assert_eq!(preview.bcc, vec!["sender@example.com"]);
+
+        // The warning email already goes to the sender
+        let preview = config.preview_email(Email::Warning).unwrap();
+        assert!(preview.bcc.is_empty());
+    }
+
+    #[test]
+    fn test_warning_notify_recipients() {
+        let mut config = get_test_config();
+        let preview = config.preview_email(Email::Warning).unwrap();
+        assert_eq!(preview.to, vec!["sender@example.com"]);
+
+        config.warning_notify_recipients = true;
+        let preview = config.preview_email(Email::Warning).unwrap();
+        assert_eq!(
+            preview.to,
+            vec!["sender@example.com", "recipient@example.com"]
+        );
+        assert_eq!(preview.subject, config.subject_warning);
+
+        // Only the warning goes to the recipients too
+        let preview = config.preview_email(Email::FinalGrace).unwrap();
+        assert_eq!(preview.to, vec!["sender@example.com"]);
+    }
 }
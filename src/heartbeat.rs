@@ -0,0 +1,91 @@
+//! Heartbeat for the Dead Man's Switch.
+//!
+//! The switch covers "I stopped checking in", but not "the switch itself died".
+//! If configured, a background thread pings the `heartbeat_url` every
+//! `heartbeat_interval_seconds`, so that an external service
+//! (e.g. [healthchecks.io](https://healthchecks.io)) can alert someone
+//! when the pings stop.
+
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use thiserror::Error;
+
+use crate::config::Config;
+
+/// Heartbeat errors.
+#[derive(Error, Debug)]
+pub enum HeartbeatError {
+    /// Error when pinging the heartbeat URL.
+    #[error(transparent)]
+    HttpError(#[from] Box<ureq::Error>),
+}
+
+/// Ping the heartbeat URL once.
+///
+/// ## Errors
+///
+/// - If the request fails or the server responds with an error status.
+pub fn ping(url: &str) -> Result<(), HeartbeatError> {
+    ureq::get(url).call().map_err(Box::new)?;
+    Ok(())
+}
+
+/// Spawn the heartbeat thread if a `heartbeat_url` is configured.
+///
+/// The thread pings the URL every `heartbeat_interval_seconds` for as long
+/// as the process is alive.
+/// Failed pings are ignored: the external service will notice the
+/// missing pings on its own.
+pub fn spawn(config: &Config) -> Option<JoinHandle<()>> {
+    let url = config.heartbeat_url.clone()?;
+    let interval = Duration::from_secs(config.heartbeat_interval_seconds);
+
+    thread::Builder::new()
+        .name("heartbeat".to_string())
+        .spawn(move || loop {
+            let _ = ping(&url);
+            thread::sleep(interval);
+        })
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_ping() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/ping", listener.local_addr().unwrap());
+
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request_line = String::new();
+            BufReader::new(&stream)
+                .read_line(&mut request_line)
+                .unwrap();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+                .unwrap();
+            request_line
+        });
+
+        ping(&url).unwrap();
+        assert!(server.join().unwrap().starts_with("GET /ping"));
+    }
+
+    #[test]
+    fn test_ping_unreachable() {
+        // Nothing listens on the discard port
+        assert!(ping("http://127.0.0.1:9/").is_err());
+    }
+
+    #[test]
+    fn test_spawn_without_url() {
+        let config = Config::default();
+        assert!(spawn(&config).is_none());
+    }
+}
@@ -0,0 +1,239 @@
+//! Command hook run when the Dead Man's Switch fires.
+//!
+//! If `on_deadman_command` is set and `allow_command_hooks` is enabled,
+//! the command runs after the dead man's email is sent, e.g. to publish a
+//! pre-staged file or revoke credentials.
+//!
+//! ## Security
+//!
+//! The command runs unattended, through the shell, with the privileges of
+//! the switch.
+//! Anyone who can edit the `config.toml` can make it run anything,
+//! and a switch fired by mistake runs it anyway, so hooks are off unless
+//! `allow_command_hooks` is explicitly set.
+
+use std::fs::OpenOptions;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use chrono::Local;
+use thiserror::Error;
+
+use crate::config::{log_dry_run_skipped, Config};
+
+/// Interval between checks for the command to finish.
+const WAIT_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Hook errors.
+#[derive(Error, Debug)]
+pub enum HookError {
+    /// IO Error.
+    #[error(transparent)]
+    IoError(#[from] io::Error),
+    /// The command didn't finish in time and was killed.
+    #[error("the command didn't finish within {0:?} and was killed")]
+    Timeout(Duration),
+}
+
+/// The outcome of a finished hook command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookOutput {
+    /// The exit status of the command.
+    pub status: ExitStatus,
+    /// What the command wrote to stdout.
+    pub stdout: String,
+    /// What the command wrote to stderr.
+    pub stderr: String,
+}
+
+/// Run the `on_deadman_command`, if configured and allowed.
+///
+/// The command gets the `DMS_EVENT` (`dead_man`) and `DMS_FIRED_AT`
+/// (Unix timestamp) environment variables,
+/// and is killed after `command_timeout_seconds`.
+///
+/// Returns `None` if there is no command to run.
+/// In a dry run, see [`Config::dry_run`], the command isn't run either,
+/// and only logged to the `dry_run.log`.
+///
+/// ## Errors
+///
+/// - If the command cannot be started.
+/// - If the command doesn't finish in time.
+pub fn run_deadman_hook(
+    config: &Config,
+    fired_at: SystemTime,
+) -> Result<Option<HookOutput>, HookError> {
+    let command = match &config.on_deadman_command {
+        Some(command) if config.allow_command_hooks => command,
+        _ => return Ok(None),
+    };
+    if config.dry_run {
+        log_dry_run_skipped(&format!("not run: on_deadman_command {command:?}"))?;
+        return Ok(None);
+    }
+    let fired_at = fired_at
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+        .to_string();
+    let env = [
+        ("DMS_EVENT", "dead_man"),
+        ("DMS_FIRED_AT", fired_at.as_str()),
+    ];
+    let timeout = Duration::from_secs(config.command_timeout_seconds);
+
+    run_command(command, &env, timeout).map(Some)
+}
+
+/// Append the outcome of a hook to the log file.
+///
+/// Does nothing if no hook ran.
+///
+/// ## Errors
+///
+/// - If the log file cannot be written.
+pub fn log(path: &Path, result: &Result<Option<HookOutput>, HookError>) -> io::Result<()> {
+    let entry = match result {
+        Ok(None) => return Ok(()),
+        Ok(Some(output)) => format!(
+            "on_deadman_command exited with {}\n--- stdout ---\n{}\n--- stderr ---\n{}",
+            output.status, output.stdout, output.stderr
+        ),
+        Err(error) => format!("on_deadman_command failed: {error}"),
+    };
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "[{}] {}", Local::now().to_rfc3339(), entry)
+}
+
+/// Run the command through the shell, killing it after the timeout.
+pub(crate) fn run_command(
+    command: &str,
+    env: &[(&str, &str)],
+    timeout: Duration,
+) -> Result<HookOutput, HookError> {
+    let output = run_command_raw(command, env, timeout)?;
+    Ok(HookOutput {
+        status: output.status,
+        stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+    })
+}
+
+/// Run the command like [`run_command`], keeping its output as bytes.
+pub(crate) fn run_command_raw(
+    command: &str,
+    env: &[(&str, &str)],
+    timeout: Duration,
+) -> Result<Output, HookError> {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    let mut child = shell
+        .arg(command)
+        .envs(env.iter().copied())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // Read the output while waiting, so a chatty command can't fill the pipes
+    let stdout = read_to_end(child.stdout.take());
+    let stderr = read_to_end(child.stderr.take());
+
+    let status = wait_timeout(&mut child, timeout)?;
+    Ok(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+/// Read the pipe to the end on a separate thread.
+fn read_to_end<R: Read + Send + 'static>(pipe: Option<R>) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut output = vec![];
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut output);
+        }
+        output
+    })
+}
+
+/// Wait for the child to exit, killing it after the timeout.
+fn wait_timeout(child: &mut Child, timeout: Duration) -> Result<ExitStatus, HookError> {
+    let start = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+        if start.elapsed() >= timeout {
+            child.kill()?;
+            child.wait()?;
+            return Err(HookError::Timeout(timeout));
+        }
+        thread::sleep(WAIT_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hooks_need_to_be_allowed() {
+        let config = Config {
+            on_deadman_command: Some("echo fired".to_string()),
+            ..Config::default()
+        };
+        assert!(!config.allow_command_hooks);
+        assert!(run_deadman_hook(&config, SystemTime::now())
+            .unwrap()
+            .is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_deadman_hook() {
+        let config = Config {
+            on_deadman_command: Some("echo $DMS_EVENT $DMS_FIRED_AT; echo oops >&2".to_string()),
+            allow_command_hooks: true,
+            ..Config::default()
+        };
+        let fired_at = UNIX_EPOCH + Duration::from_secs(42);
+
+        let output = run_deadman_hook(&config, fired_at).unwrap().unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout, "dead_man 42\n");
+        assert_eq!(output.stderr, "oops\n");
+    }
+
+    #[test]
+    fn test_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hook.log");
+
+        log(&path, &Ok(None)).unwrap();
+        assert!(!path.exists());
+
+        log(&path, &Err(HookError::Timeout(Duration::from_secs(1)))).unwrap();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(contents.contains("on_deadman_command failed"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_command_timeout() {
+        let result = run_command("sleep 5", &[], Duration::from_millis(100));
+        assert!(matches!(result, Err(HookError::Timeout(_))));
+    }
+}
@@ -0,0 +1,263 @@
+//! Localization of the user-facing strings.
+//!
+//! Each supported [`Language`] has a [`Messages`] catalog with the
+//! UI strings and the units used to format durations.
+//! English is the default.
+
+use serde::{Deserialize, Serialize};
+
+/// Supported languages.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Language {
+    /// English.
+    #[default]
+    #[serde(rename = "en")]
+    English,
+    /// Portuguese.
+    #[serde(rename = "pt")]
+    Portuguese,
+}
+
+/// Units used when formatting durations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeUnit {
+    /// Days.
+    Day,
+    /// Hours.
+    Hour,
+    /// Minutes.
+    Minute,
+    /// Seconds.
+    Second,
+}
+
+/// The message catalog of a [`Language`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Messages {
+    /// Singular and plural forms of [`TimeUnit::Day`].
+    pub day: (&'static str, &'static str),
+    /// Singular and plural forms of [`TimeUnit::Hour`].
+    pub hour: (&'static str, &'static str),
+    /// Singular and plural forms of [`TimeUnit::Minute`].
+    pub minute: (&'static str, &'static str),
+    /// Singular and plural forms of [`TimeUnit::Second`].
+    pub second: (&'static str, &'static str),
+    /// A duration under a minute, where the seconds don't matter.
+    pub less_than_a_minute: &'static str,
+    /// Title of the keys legend.
+    pub keys: &'static str,
+    /// Check-in action in the keys legend.
+    pub check_in: &'static str,
+    /// Quit action in the keys legend.
+    pub quit: &'static str,
+    /// Enable/disable action in the keys legend.
+    pub toggle: &'static str,
+    /// Pause/resume action in the keys legend.
+    pub pause: &'static str,
+    /// Test email action in the keys legend.
+    pub test_email: &'static str,
+    /// Status while the test email is being sent.
+    pub test_email_sending: &'static str,
+    /// Status after the test email was sent, before the address.
+    pub test_email_sent: &'static str,
+    /// Status after the test email failed, before the error.
+    pub test_email_failed: &'static str,
+    /// Extend action in the keys legend.
+    pub extend: &'static str,
+    /// Question for the duration to extend the deadline by, before the input.
+    pub extend_by: &'static str,
+    /// Status after the deadline was extended, before the duration.
+    pub extended: &'static str,
+    /// Status after an invalid duration was typed in, before it.
+    pub invalid_duration: &'static str,
+    /// Title of the instructions block.
+    pub instructions: &'static str,
+    /// First instruction, before the config path.
+    pub edit_config: &'static str,
+    /// First instruction, after the config path.
+    pub modify_settings: &'static str,
+    /// Second instruction, before the check-in key.
+    pub check_in_with: &'static str,
+    /// Second instruction, after the check-in key.
+    pub within_warning_time: &'static str,
+    /// Third instruction.
+    pub otherwise: &'static str,
+    /// Label of the last check-in, before its date.
+    pub last_check_in: &'static str,
+    /// Date of the last check-in when there was none.
+    pub never: &'static str,
+    /// Label of the date the dead man's email is due, before the date.
+    pub fires_on: &'static str,
+    /// Title of the timer block.
+    pub timer: &'static str,
+    /// Title of the warning timer.
+    pub warning: &'static str,
+    /// Title of an escalation timer, before its number.
+    pub escalation: &'static str,
+    /// Title of the dead man's timer.
+    pub dead_man: &'static str,
+    /// Label of a timer that isn't armed yet.
+    pub not_armed: &'static str,
+    /// Title of the timer once the switch fired, while the dead man's email
+    /// is resent.
+    pub resending: &'static str,
+    /// Line of the resent dead man's email, before the acknowledgment link.
+    pub acknowledge: &'static str,
+    /// Desktop notification once the warning was sent.
+    pub warning_alert: &'static str,
+    /// Label prefix of a paused timer.
+    pub paused: &'static str,
+    /// Title suffix of a disabled switch.
+    pub disabled: &'static str,
+    /// Question before quitting.
+    pub confirm_quit: &'static str,
+    /// Title of the notifications log block.
+    pub log: &'static str,
+    /// Log entry of a notification that was sent.
+    pub sent: &'static str,
+    /// Log entry of a notification that failed, before the error.
+    pub failed: &'static str,
+    /// Title of the config warnings block.
+    pub warnings: &'static str,
+}
+
+/// English message catalog.
+const ENGLISH: Messages = Messages {
+    day: ("day", "days"),
+    hour: ("hour", "hours"),
+    minute: ("minute", "minutes"),
+    second: ("second", "seconds"),
+    less_than_a_minute: "less than a minute",
+    keys: "Keys",
+    check_in: "Check-In",
+    quit: "Quit",
+    toggle: "Enable/Disable",
+    pause: "Pause/Resume",
+    test_email: "Test Email",
+    test_email_sending: "Sending the test email...",
+    test_email_sent: "Test email sent to ",
+    test_email_failed: "Test email failed: ",
+    extend: "Extend",
+    extend_by: "Extend by, e.g. 3d or 12h (Enter/Esc): ",
+    extended: "Deadline extended by ",
+    invalid_duration: "Invalid duration, expected e.g. 3d or 12h: ",
+    instructions: "Instructions",
+    edit_config: "Edit the Config at ",
+    modify_settings: " and modify the settings.",
+    check_in_with: "Check-In with ",
+    within_warning_time: " within the warning time.",
+    otherwise: "Otherwise the Dead Man's Switch will be triggered and the message with optional attachment will be sent.",
+    last_check_in: "Last check-in: ",
+    never: "never",
+    fires_on: "Fires on ",
+    timer: "Timer",
+    warning: "Warning",
+    escalation: "Escalation",
+    dead_man: "Dead Man's Switch",
+    not_armed: "Not armed",
+    resending: "Fired, resending until acknowledged",
+    acknowledge: "Please confirm that you received this email, or it will be sent again: ",
+    warning_alert: "The warning was sent, check in to stop the switch",
+    paused: "PAUSED",
+    disabled: "Disabled",
+    confirm_quit: "Quit? The switch will stop running. (y/n)",
+    log: "Log",
+    sent: "sent",
+    failed: "failed: ",
+    warnings: "Warnings",
+};
+
+/// Portuguese message catalog.
+const PORTUGUESE: Messages = Messages {
+    day: ("dia", "dias"),
+    hour: ("hora", "horas"),
+    minute: ("minuto", "minutos"),
+    second: ("segundo", "segundos"),
+    less_than_a_minute: "menos de um minuto",
+    keys: "Teclas",
+    check_in: "Check-In",
+    quit: "Sair",
+    toggle: "Ativar/Desativar",
+    pause: "Pausar/Retomar",
+    test_email: "Email de Teste",
+    test_email_sending: "Enviando o email de teste...",
+    test_email_sent: "Email de teste enviado para ",
+    test_email_failed: "Falha no email de teste: ",
+    extend: "Estender",
+    extend_by: "Estender por, ex. 3d ou 12h (Enter/Esc): ",
+    extended: "Prazo estendido por ",
+    invalid_duration: "Duração inválida, esperado ex. 3d ou 12h: ",
+    instructions: "Instruções",
+    edit_config: "Edite a configuração em ",
+    modify_settings: " e modifique as opções.",
+    check_in_with: "Faça o Check-In com ",
+    within_warning_time: " dentro do tempo de aviso.",
+    otherwise: "Caso contrário, o Dead Man's Switch será acionado e a mensagem com o anexo opcional será enviada.",
+    last_check_in: "Último check-in: ",
+    never: "nunca",
+    fires_on: "Dispara em ",
+    timer: "Temporizador",
+    warning: "Aviso",
+    escalation: "Escalonamento",
+    dead_man: "Dead Man's Switch",
+    not_armed: "Não armado",
+    resending: "Disparado, reenviando até a confirmação",
+    acknowledge: "Por favor, confirme que recebeu este email, ou ele será enviado novamente: ",
+    warning_alert: "O aviso foi enviado, faça o check-in para parar o switch",
+    paused: "PAUSADO",
+    disabled: "Desativado",
+    confirm_quit: "Sair? O switch vai parar de rodar. (y/n)",
+    log: "Registro",
+    sent: "enviado",
+    failed: "falhou: ",
+    warnings: "Alertas",
+};
+
+impl Language {
+    /// Get the message catalog of the language.
+    pub fn messages(&self) -> &'static Messages {
+        match self {
+            Language::English => &ENGLISH,
+            Language::Portuguese => &PORTUGUESE,
+        }
+    }
+
+    /// Format a quantity of a [`TimeUnit`], pluralizing the unit as needed.
+    pub fn unit(&self, unit: TimeUnit, quantity: i64) -> String {
+        let messages = self.messages();
+        let (singular, plural) = match unit {
+            TimeUnit::Day => messages.day,
+            TimeUnit::Hour => messages.hour,
+            TimeUnit::Minute => messages.minute,
+            TimeUnit::Second => messages.second,
+        };
+        let unit = if quantity == 1 { singular } else { plural };
+        format!("{quantity} {unit}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unit_pluralization() {
+        assert_eq!(Language::English.unit(TimeUnit::Day, 1), "1 day");
+        assert_eq!(Language::English.unit(TimeUnit::Day, 2), "2 days");
+        assert_eq!(Language::English.unit(TimeUnit::Second, 0), "0 seconds");
+        assert_eq!(Language::Portuguese.unit(TimeUnit::Hour, 1), "1 hora");
+        assert_eq!(Language::Portuguese.unit(TimeUnit::Minute, 5), "5 minutos");
+    }
+
+    #[test]
+    fn test_language_codes() {
+        #[derive(Deserialize)]
+        struct Test {
+            language: Language,
+        }
+        let test: Test = toml::from_str("language = \"pt\"").unwrap();
+        assert_eq!(test.language, Language::Portuguese);
+        assert!(toml::from_str::<Test>("language = \"xx\"").is_err());
+    }
+}
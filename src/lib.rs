@@ -6,11 +6,22 @@
 //! Use at your own risk.
 //! Check the f*(as in friendly) code.
 
+pub mod api;
 pub mod config;
 pub mod email;
+pub mod heartbeat;
+pub mod hook;
+pub mod i18n;
+pub mod metrics;
+pub mod notify;
+pub mod signal;
+pub mod telegram;
+#[cfg(test)]
+mod test_util;
 pub mod timer;
 #[cfg(feature = "tui")]
 pub mod tui;
+pub mod webhook;
 
 #[cfg(feature = "tui")]
 pub use tui::run;
@@ -7,16 +7,295 @@
 //! Check the f****(as in friendly) code.
 
 use std::error::Error;
+#[cfg(feature = "tui")]
+use std::io::{self, BufRead, Write};
+#[cfg(feature = "tui")]
+use std::path::PathBuf;
+#[cfg(feature = "tui")]
+use std::process;
+#[cfg(feature = "tui")]
+use std::time::SystemTime;
 
 #[cfg(feature = "tui")]
-use dead_man_switch::run;
+use chrono::{DateTime, Local};
+#[cfg(feature = "tui")]
+use clap::{Parser, Subcommand};
+#[cfg(feature = "tui")]
+use dead_man_switch::{
+    config::{
+        config_path, generate_config, hook_log_path, load_or_initialize_config,
+        log_dry_run_skipped, log_wipe, profile, state_path, wipe_profile, Config, ConfigError,
+        Email, ENV_PREFIX, PROFILE_ENV,
+    },
+    hook,
+    notify::{email_error, send_all},
+    run,
+    timer::{CheckInSource, Timer, TimerType},
+};
+
+/// The command line interface.
+#[cfg(feature = "tui")]
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Cli {
+    /// The command to run.
+    ///
+    /// Without a command the TUI is launched.
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Log the emails to `dry_run.log` next to the config instead of
+    /// sending them.
+    #[arg(long, global = true)]
+    dry_run: bool,
+    /// The profile of the config to use, a `[profiles.<name>]` table.
+    #[arg(long, global = true, value_name = "NAME")]
+    profile: Option<String>,
+}
+
+/// The CLI commands.
+#[cfg(feature = "tui")]
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Launch the TUI, the default.
+    Run,
+    /// Print the state of the timer.
+    Status,
+    /// Check in, restarting the warning timer.
+    ///
+    /// A running switch picks the check-in up from the saved state.
+    CheckIn,
+    /// Immediately send the dead man's email.
+    ///
+    /// This cannot be undone and asks for confirmation twice.
+    Trigger,
+    /// Send a test email to `from` to check the SMTP settings.
+    #[command(alias = "send-test")]
+    TestEmail,
+    /// Write the default config and exit.
+    ///
+    /// The format follows the extension, e.g. `config.yaml`.
+    GenerateConfig {
+        /// Where to write the config, the default config path if not given.
+        path: Option<PathBuf>,
+        /// Overwrite an existing config.
+        #[arg(long)]
+        force: bool,
+    },
+    /// Print the JSON Schema of the config.
+    #[cfg(feature = "schema")]
+    Schema,
+}
 
 /// The main function.
 ///
 /// This function executes the main loop of the application
-/// by calling the [`run`] function.
+/// by calling the [`run`] function, unless another command is given.
 #[cfg(feature = "tui")]
 fn main() -> Result<(), Box<dyn Error>> {
-    run()?;
+    let cli = Cli::parse();
+    if cli.dry_run {
+        // Applied to the config like any other override
+        std::env::set_var(format!("{ENV_PREFIX}DRY_RUN"), "true");
+    }
+    if let Some(profile) = &cli.profile {
+        std::env::set_var(PROFILE_ENV, profile);
+    }
+    match cli.command {
+        None | Some(Command::Run) => run()?,
+        Some(Command::Status) => status()?,
+        Some(Command::CheckIn) => check_in()?,
+        Some(Command::Trigger) => trigger()?,
+        Some(Command::TestEmail) => test_email()?,
+        Some(Command::GenerateConfig { path, force }) => generate(path, force)?,
+        #[cfg(feature = "schema")]
+        Some(Command::Schema) => println!("{}", dead_man_switch::config::config_schema()),
+    }
+    Ok(())
+}
+
+/// Print the type and remaining time of the saved timer.
+#[cfg(feature = "tui")]
+fn status() -> Result<(), Box<dyn Error>> {
+    let config = load_or_initialize_config()?;
+    let messages = config.language.messages();
+    let timer =
+        Timer::load(&state_path()?, &config)?.unwrap_or_else(|| Timer::unarmed(config.clock));
+
+    // An unarmed timer's label already says so
+    if timer.get_type() == TimerType::Disabled {
+        println!("{}", timer.label(config.language));
+    } else {
+        println!(
+            "{}: {}",
+            timer.title(messages),
+            timer.label(config.language)
+        );
+    }
+    if !config.enabled {
+        println!("{}", messages.disabled);
+    } else if let Some(deadline) = timer.deadline(&config) {
+        println!(
+            "{}{}",
+            messages.fires_on,
+            DateTime::<Local>::from(deadline).format("%Y-%m-%d %H:%M")
+        );
+    }
+    let last_check_in = match timer.last_check_in() {
+        Some(last_check_in) => DateTime::<Local>::from(last_check_in)
+            .format("%Y-%m-%d %H:%M")
+            .to_string(),
+        None => messages.never.to_string(),
+    };
+    println!("{}{last_check_in}", messages.last_check_in);
+    Ok(())
+}
+
+/// Check in on the saved timer.
+///
+/// A running switch notices the saved check-in and checks in too.
+#[cfg(feature = "tui")]
+fn check_in() -> Result<(), Box<dyn Error>> {
+    let config = load_or_initialize_config()?;
+    let state_path = state_path()?;
+    let mut timer =
+        Timer::load(&state_path, &config)?.unwrap_or_else(|| Timer::unarmed(config.clock));
+
+    timer.check_in(&config, CheckInSource::Cli);
+    timer.save(&state_path)?;
+    println!(
+        "Checked in: {}: {}",
+        timer.title(config.language.messages()),
+        timer.label(config.language)
+    );
     Ok(())
 }
+
+/// Send the dead man's email right now, after a double confirmation.
+///
+/// Fires the saved timer like the TUI does: with `dead_man_resends` the
+/// timer resends the email until acknowledged, otherwise it rests until
+/// the next check-in, so a running or relaunched TUI doesn't send it again.
+#[cfg(feature = "tui")]
+fn trigger() -> Result<(), Box<dyn Error>> {
+    let config = load_or_initialize_config()?;
+
+    let answer = prompt(&format!(
+        "This will immediately send the dead man's email to {}. Continue? [y/N] ",
+        config.to
+    ))?;
+    if !answer.eq_ignore_ascii_case("y") {
+        println!("Aborted.");
+        return Ok(());
+    }
+    let answer = prompt("This cannot be undone. Type TRIGGER to confirm: ")?;
+    if answer != "TRIGGER" {
+        println!("Aborted.");
+        return Ok(());
+    }
+
+    // Fire the saved timer, so the email carries the acknowledgment link
+    let state_path = state_path()?;
+    let mut timer =
+        Timer::load(&state_path, &config)?.unwrap_or_else(|| Timer::unarmed(config.clock));
+    timer.fire(&config)?;
+    let config = Config {
+        acknowledge_url: timer.acknowledge_url(&config),
+        ..config
+    };
+
+    // The other channels and the personal emails failing doesn't make the
+    // trigger fail, the dead man's email does
+    let errors = send_all(&config.channels(), &config.event(Email::DeadMan));
+    let warnings: Vec<String> = errors.iter().map(ToString::to_string).collect();
+    if let Some(error) = email_error(errors) {
+        return Err(error.into());
+    }
+    for warning in warnings {
+        eprintln!("Warning: {warning}");
+    }
+    if config.dry_run {
+        println!(
+            "Dry run: the dead man's email to {} was written to dry_run.log, the timer is unchanged.",
+            config.to
+        );
+    } else {
+        if timer.resending(&config) {
+            timer.save(&state_path)?;
+        } else {
+            Timer::unarmed(config.clock).save(&state_path)?;
+        }
+        println!(
+            "DEAD MAN'S SWITCH TRIGGERED: the dead man's email was sent to {}.",
+            config.to
+        );
+    }
+    hook::log(
+        &hook_log_path()?,
+        &hook::run_deadman_hook(&config, SystemTime::now()),
+    )?;
+    if config.wipe_config_on_fire {
+        let config_path = config_path()?;
+        let profile = profile()?;
+        let wiped = format!(
+            "the profile {profile:?} of the config {}",
+            config_path.display()
+        );
+        if config.dry_run {
+            log_dry_run_skipped(&format!("not wiped: {wiped}"))?;
+            println!("Dry run, didn't wipe {wiped}.");
+            return Ok(());
+        }
+        let result = wipe_profile(&config_path, &profile);
+        log_wipe(&hook_log_path()?, &config_path, &profile, &result)?;
+        match result {
+            Ok(()) => println!("Wiped {wiped}."),
+            Err(error) => eprintln!("Failed to wipe the config: {error}"),
+        }
+    }
+    Ok(())
+}
+
+/// Write the default config to `path`, or the default config path.
+#[cfg(feature = "tui")]
+fn generate(path: Option<PathBuf>, force: bool) -> Result<(), Box<dyn Error>> {
+    let path = match path {
+        Some(path) => path,
+        None => config_path()?,
+    };
+    match generate_config(&path, force) {
+        Err(error @ ConfigError::AlreadyExists(_)) => {
+            eprintln!("{error}, pass --force to overwrite it.");
+            process::exit(1);
+        }
+        result => result?,
+    }
+    println!("Wrote the default config to {}.", path.display());
+    Ok(())
+}
+
+/// Send the test email, printing whether it worked.
+#[cfg(feature = "tui")]
+fn test_email() -> Result<(), Box<dyn Error>> {
+    let config = load_or_initialize_config()?;
+
+    match config.send_test_email() {
+        Ok(()) => {
+            println!("Test email sent to {}.", config.from);
+            Ok(())
+        }
+        Err(error) => {
+            eprintln!("Test email failed: {error}");
+            process::exit(1);
+        }
+    }
+}
+
+/// Prompt the user and read a line from stdin.
+#[cfg(feature = "tui")]
+fn prompt(message: &str) -> io::Result<String> {
+    print!("{message}");
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(answer.trim().to_string())
+}
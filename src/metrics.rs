@@ -0,0 +1,234 @@
+//! Prometheus metrics of the Dead Man's Switch.
+//!
+//! If `metrics_listen` is set, `GET /metrics` on that address serves the
+//! state of the timer and counters of the emails sent,
+//! in the Prometheus text format, see [`spawn`].
+//!
+//! The endpoint is unauthenticated, so bind it to an internal address.
+
+use std::fmt::Write;
+use std::io;
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+
+use crate::api::{read_request, respond, serve_connections};
+use crate::config::Config;
+use crate::notify::ChannelError;
+use crate::timer::{TimerSnapshot, TimerType};
+
+/// The path of the metrics endpoint.
+const METRICS_PATH: &str = "/metrics";
+
+/// The content type of the Prometheus text format.
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// The metrics of the switch.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Metrics {
+    /// The type of the current timer, if known yet.
+    timer_type: Option<TimerType>,
+    /// Seconds until the current timer expires.
+    remaining_seconds: u64,
+    /// The remaining time as a percentage of the current timer.
+    remaining_percent: u16,
+    /// Whether the countdown is paused.
+    paused: bool,
+    /// Whether the switch is enabled.
+    enabled: bool,
+    /// Emails sent.
+    emails_sent: u64,
+    /// Emails that failed to send.
+    email_failures: u64,
+}
+
+impl Metrics {
+    /// Update the metrics of the timer, given its [`TimerSnapshot`].
+    pub fn update(&mut self, snapshot: &TimerSnapshot, enabled: bool) {
+        self.timer_type = Some(snapshot.timer_type);
+        self.remaining_seconds = snapshot.remaining_seconds;
+        self.remaining_percent = snapshot.remaining_percent;
+        self.paused = snapshot.paused;
+        self.enabled = enabled;
+    }
+
+    /// Count a notification as an email sent or failed,
+    /// given the errors of the notification channels.
+    pub fn record_notification(&mut self, errors: &[ChannelError]) {
+        if errors
+            .iter()
+            .any(|error| matches!(error, ChannelError::EmailError(_)))
+        {
+            self.email_failures += 1;
+        } else {
+            self.emails_sent += 1;
+        }
+    }
+
+    /// Render the metrics in the Prometheus text format.
+    pub fn render(&self) -> String {
+        let stages = [
+            ("warning", TimerType::Warning),
+            ("escalation", TimerType::Escalation),
+            ("dead_man", TimerType::DeadMan),
+            ("disabled", TimerType::Disabled),
+        ]
+        .iter()
+        .map(|(name, timer_type)| {
+            let current = self.timer_type == Some(*timer_type);
+            (format!("{{stage=\"{name}\"}}"), u64::from(current))
+        })
+        .collect::<Vec<_>>();
+        let value = |value: u64| vec![(String::new(), value)];
+
+        let mut metrics = String::new();
+        for (name, kind, help, samples) in [
+            (
+                "dms_remaining_seconds",
+                "gauge",
+                "Seconds until the current timer expires.",
+                value(self.remaining_seconds),
+            ),
+            (
+                "dms_remaining_percent",
+                "gauge",
+                "Remaining time as a percentage of the current timer.",
+                value(u64::from(self.remaining_percent)),
+            ),
+            (
+                "dms_stage",
+                "gauge",
+                "The current timer stage, 1 for the current one.",
+                stages,
+            ),
+            (
+                "dms_paused",
+                "gauge",
+                "Whether the countdown is paused.",
+                value(u64::from(self.paused)),
+            ),
+            (
+                "dms_enabled",
+                "gauge",
+                "Whether the switch is enabled.",
+                value(u64::from(self.enabled)),
+            ),
+            (
+                "dms_emails_sent_total",
+                "counter",
+                "Emails sent.",
+                value(self.emails_sent),
+            ),
+            (
+                "dms_email_failures_total",
+                "counter",
+                "Emails that failed to send.",
+                value(self.email_failures),
+            ),
+        ] {
+            let _ = writeln!(metrics, "# HELP {name} {help}");
+            let _ = writeln!(metrics, "# TYPE {name} {kind}");
+            for (labels, value) in samples {
+                let _ = writeln!(metrics, "{name}{labels} {value}");
+            }
+        }
+
+        metrics
+    }
+}
+
+/// Spawn the thread serving the metrics, if `metrics_listen` is set.
+///
+/// ## Errors
+///
+/// - If the `metrics_listen` address cannot be bound.
+pub fn spawn(config: &Config, metrics: Arc<Mutex<Metrics>>) -> io::Result<()> {
+    match &config.metrics_listen {
+        Some(address) => serve(TcpListener::bind(address)?, metrics),
+        None => Ok(()),
+    }
+}
+
+/// Serve the metrics on `listener` on a separate thread.
+fn serve(listener: TcpListener, metrics: Arc<Mutex<Metrics>>) -> io::Result<()> {
+    serve_connections(listener, "metrics", move |stream| {
+        // A failed request doesn't stop the endpoint
+        let _ = handle(stream, &metrics);
+    })
+}
+
+/// Handle a single request and respond to it.
+fn handle(stream: TcpStream, metrics: &Mutex<Metrics>) -> io::Result<()> {
+    let request = read_request(&stream)?;
+
+    if request.path != METRICS_PATH {
+        respond(stream, "404 Not Found", CONTENT_TYPE, "not found\n")
+    } else if request.method != "GET" {
+        respond(
+            stream,
+            "405 Method Not Allowed",
+            CONTENT_TYPE,
+            "method not allowed\n",
+        )
+    } else {
+        let body = match metrics.lock() {
+            Ok(metrics) => metrics.render(),
+            Err(poisoned) => poisoned.into_inner().render(),
+        };
+        respond(stream, "200 OK", CONTENT_TYPE, &body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::email::EmailError;
+    use crate::timer::Timer;
+    use std::time::Duration;
+
+    #[test]
+    fn test_render() {
+        let mut metrics = Metrics::default();
+        let timer = Timer::new(TimerType::Warning, Duration::from_secs(60));
+        metrics.update(&timer.snapshot(&Config::default()), true);
+        metrics.record_notification(&[]);
+        metrics.record_notification(&[ChannelError::EmailError(EmailError::UnknownGroup(
+            "family".to_string(),
+        ))]);
+
+        let rendered = metrics.render();
+        assert!(rendered.contains("# TYPE dms_remaining_seconds gauge\n"));
+        assert!(rendered.contains("dms_remaining_percent "));
+        assert!(rendered.contains("dms_stage{stage=\"warning\"} 1\n"));
+        assert!(rendered.contains("dms_stage{stage=\"dead_man\"} 0\n"));
+        assert!(rendered.contains("dms_paused 0\n"));
+        assert!(rendered.contains("dms_enabled 1\n"));
+        assert!(rendered.contains("# TYPE dms_emails_sent_total counter\n"));
+        assert!(rendered.contains("dms_emails_sent_total 1\n"));
+        assert!(rendered.contains("dms_email_failures_total 1\n"));
+    }
+
+    #[test]
+    fn test_serve() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let metrics = Arc::new(Mutex::new(Metrics::default()));
+        metrics.lock().unwrap().record_notification(&[]);
+        serve(listener, Arc::clone(&metrics)).unwrap();
+
+        let response = ureq::get(&format!("{url}{METRICS_PATH}")).call().unwrap();
+        assert_eq!(response.content_type(), "text/plain");
+        assert!(response
+            .into_string()
+            .unwrap()
+            .contains("dms_emails_sent_total 1\n"));
+
+        let not_found = ureq::get(&format!("{url}/")).call();
+        assert!(matches!(not_found, Err(ureq::Error::Status(404, _))));
+    }
+
+    #[test]
+    fn test_spawn_without_address() {
+        let metrics = Arc::new(Mutex::new(Metrics::default()));
+        spawn(&Config::default(), metrics).unwrap();
+    }
+}
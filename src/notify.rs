@@ -0,0 +1,354 @@
+//! Notification channels of the Dead Man's Switch.
+//!
+//! Every way of notifying someone, e.g. email, implements the
+//! [`NotificationChannel`] trait.
+//! The configured channels are built with [`Config::channels`] and a
+//! [`SwitchEvent`] is sent on all of them with [`send_all`],
+//! so a failing channel doesn't prevent the others from being notified.
+
+use std::io;
+use std::time::SystemTime;
+
+use thiserror::Error;
+
+use crate::config::{log_dry_run_skipped, Config, Email};
+use crate::email::EmailError;
+use crate::signal::{SignalChannel, SignalError};
+use crate::telegram::{TelegramChannel, TelegramError};
+use crate::webhook::{WebhookChannel, WebhookError};
+
+/// Notification channel errors.
+#[derive(Error, Debug)]
+pub enum ChannelError {
+    /// [`EmailError`] blanket error conversion.
+    #[error(transparent)]
+    EmailError(#[from] EmailError),
+    /// [`SignalError`] blanket error conversion.
+    #[error(transparent)]
+    SignalError(#[from] SignalError),
+    /// [`TelegramError`] blanket error conversion.
+    #[error(transparent)]
+    TelegramError(#[from] TelegramError),
+    /// [`WebhookError`] blanket error conversion.
+    #[error(transparent)]
+    WebhookError(#[from] WebhookError),
+    /// IO Error, e.g. when writing the log of a dry run.
+    #[error(transparent)]
+    IoError(#[from] io::Error),
+}
+
+/// An event of the switch that channels notify about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwitchEvent {
+    /// The kind of event, named after the email sent for it.
+    pub kind: Email,
+    /// The subject of the notification.
+    pub subject: String,
+    /// The message of the notification.
+    pub message: String,
+    /// When the event happened.
+    pub timestamp: SystemTime,
+}
+
+/// A way of notifying about [`SwitchEvent`]s.
+pub trait NotificationChannel {
+    /// The name of the channel.
+    fn name(&self) -> &str;
+
+    /// Send a notification about the event.
+    ///
+    /// ## Errors
+    ///
+    /// - If the notification could not be sent.
+    fn send(&self, event: &SwitchEvent) -> Result<(), ChannelError>;
+}
+
+/// The email channel.
+///
+/// Sends the email matching the [`SwitchEvent::kind`].
+#[derive(Debug, Clone)]
+pub struct EmailChannel {
+    /// The configuration used to send the emails.
+    config: Config,
+}
+
+impl EmailChannel {
+    /// Create a new email channel.
+    pub fn new(config: Config) -> Self {
+        EmailChannel { config }
+    }
+}
+
+impl NotificationChannel for EmailChannel {
+    fn name(&self) -> &str {
+        "email"
+    }
+
+    fn send(&self, event: &SwitchEvent) -> Result<(), ChannelError> {
+        self.config.send_email(event.kind)?;
+        Ok(())
+    }
+}
+
+/// A channel standing in for another one in a dry run,
+/// see [`Config::dry_run`].
+///
+/// Logs what would have been sent to the `dry_run.log` instead.
+#[derive(Debug, Clone)]
+pub struct DryRunChannel {
+    /// The name of the channel it stands in for.
+    channel: String,
+    /// The name of this channel.
+    name: String,
+}
+
+impl DryRunChannel {
+    /// Create a channel standing in for the `channel`.
+    pub fn new(channel: &dyn NotificationChannel) -> Self {
+        DryRunChannel {
+            channel: channel.name().to_string(),
+            name: format!("{} (dry run)", channel.name()),
+        }
+    }
+}
+
+impl NotificationChannel for DryRunChannel {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn send(&self, event: &SwitchEvent) -> Result<(), ChannelError> {
+        log_dry_run_skipped(&format!(
+            "not sent over {}: {}",
+            self.channel, event.subject
+        ))?;
+        Ok(())
+    }
+}
+
+impl Config {
+    /// Create the [`SwitchEvent`] for the given kind, now.
+    pub fn event(&self, kind: Email) -> SwitchEvent {
+        SwitchEvent {
+            kind,
+            subject: self.subject(kind),
+            message: self.body(kind),
+            timestamp: SystemTime::now(),
+        }
+    }
+
+    /// Build the configured notification channels.
+    ///
+    /// In a dry run, the email channel logs the emails itself,
+    /// and the other channels are replaced by [`DryRunChannel`]s.
+    pub fn channels(&self) -> Vec<Box<dyn NotificationChannel>> {
+        let mut channels: Vec<Box<dyn NotificationChannel>> =
+            vec![Box::new(EmailChannel::new(self.clone()))];
+        let mut push = |channel: Box<dyn NotificationChannel>| {
+            if self.dry_run {
+                channels.push(Box::new(DryRunChannel::new(channel.as_ref())));
+            } else {
+                channels.push(channel);
+            }
+        };
+        if let Some(signal) = &self.signal {
+            push(Box::new(SignalChannel::new(signal.clone())));
+        }
+        if let (Some(bot_token), Some(chat_id)) = (&self.telegram_bot_token, &self.telegram_chat_id)
+        {
+            push(Box::new(TelegramChannel::new(
+                bot_token.clone(),
+                chat_id.clone(),
+            )));
+        }
+        if let Some(url) = &self.webhook_url {
+            push(Box::new(WebhookChannel::new(url.clone())));
+        }
+        channels
+    }
+}
+
+/// Send the event on all channels.
+///
+/// Every channel is tried, even if some of them fail.
+/// Returns the errors of the channels that failed.
+pub fn send_all(
+    channels: &[Box<dyn NotificationChannel>],
+    event: &SwitchEvent,
+) -> Vec<ChannelError> {
+    channels
+        .iter()
+        .filter_map(|channel| channel.send(event).err())
+        .collect()
+}
+
+/// The error of the email channel among the `errors` of [`send_all`], if any.
+///
+/// Only the email failing fails a notification,
+/// the other channels and the personal emails are best effort,
+/// see [`EmailError::only_personal`].
+pub fn email_error(errors: Vec<ChannelError>) -> Option<EmailError> {
+    errors.into_iter().find_map(|error| match error {
+        ChannelError::EmailError(error) if !error.only_personal() => Some(error),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signal::SignalConfig;
+    use std::cell::RefCell;
+    use std::io::{Error as IoError, ErrorKind as IoErrorKind};
+    use std::rc::Rc;
+
+    /// A channel that records the events it is sent.
+    struct RecordingChannel(Rc<RefCell<Vec<SwitchEvent>>>);
+
+    impl NotificationChannel for RecordingChannel {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        fn send(&self, event: &SwitchEvent) -> Result<(), ChannelError> {
+            self.0.borrow_mut().push(event.clone());
+            Ok(())
+        }
+    }
+
+    /// A channel that always fails.
+    struct FailingChannel;
+
+    impl NotificationChannel for FailingChannel {
+        fn name(&self) -> &str {
+            "failing"
+        }
+
+        fn send(&self, _event: &SwitchEvent) -> Result<(), ChannelError> {
+            Err(EmailError::IoError(IoError::from(IoErrorKind::ConnectionRefused)).into())
+        }
+    }
+
+    #[test]
+    fn test_event() {
+        let config = Config::default();
+        let event = config.event(Email::DeadMan);
+        assert_eq!(event.kind, Email::DeadMan);
+        assert_eq!(event.subject, config.subject);
+        assert_eq!(event.message, config.message);
+    }
+
+    #[test]
+    fn test_channels() {
+        let config = Config::default();
+        let names: Vec<_> = config
+            .channels()
+            .iter()
+            .map(|channel| channel.name().to_string())
+            .collect();
+        assert_eq!(names, vec!["email"]);
+
+        let config = Config {
+            signal: Some(SignalConfig {
+                url: "http://localhost:8080".to_string(),
+                number: "+15550000000".to_string(),
+                recipients: vec![],
+                owner: None,
+            }),
+            ..Config::default()
+        };
+        let names: Vec<_> = config
+            .channels()
+            .iter()
+            .map(|channel| channel.name().to_string())
+            .collect();
+        assert_eq!(names, vec!["email", "signal"]);
+
+        let config = Config {
+            telegram_bot_token: Some("123:abc".to_string()),
+            ..Config::default()
+        };
+        assert_eq!(config.channels().len(), 1);
+        let config = Config {
+            telegram_chat_id: Some("42".to_string()),
+            ..config
+        };
+        let names: Vec<_> = config
+            .channels()
+            .iter()
+            .map(|channel| channel.name().to_string())
+            .collect();
+        assert_eq!(names, vec!["email", "telegram"]);
+
+        let config = Config {
+            dry_run: true,
+            webhook_url: Some("https://example.com/hook".to_string()),
+            ..config
+        };
+        let names: Vec<_> = config
+            .channels()
+            .iter()
+            .map(|channel| channel.name().to_string())
+            .collect();
+        assert_eq!(
+            names,
+            vec!["email", "telegram (dry run)", "webhook (dry run)"]
+        );
+    }
+
+    #[test]
+    fn test_send_all_continues_after_failure() {
+        let events = Rc::new(RefCell::new(vec![]));
+        let channels: Vec<Box<dyn NotificationChannel>> = vec![
+            Box::new(FailingChannel),
+            Box::new(RecordingChannel(events.clone())),
+        ];
+        let event = Config::default().event(Email::Warning);
+
+        let errors = send_all(&channels, &event);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(*events.borrow(), vec![event]);
+    }
+
+    /// A transport failure of an HTTP channel.
+    fn http_error() -> Box<ureq::Error> {
+        Box::new(IoError::from(IoErrorKind::ConnectionRefused).into())
+    }
+
+    #[test]
+    fn test_email_error() {
+        let errors = vec![WebhookError::HttpError(http_error()).into()];
+        assert!(email_error(errors).is_none());
+        let errors = vec![TelegramError::HttpError(http_error()).into()];
+        assert!(email_error(errors).is_none());
+        let errors = vec![
+            SignalError::HttpError(http_error()).into(),
+            SignalError::IoError(IoError::from(IoErrorKind::UnexpectedEof)).into(),
+        ];
+        assert!(email_error(errors).is_none());
+
+        let errors = vec![
+            WebhookError::HttpError(http_error()).into(),
+            EmailError::IoError(IoError::from(IoErrorKind::ConnectionRefused)).into(),
+        ];
+        assert!(matches!(
+            email_error(errors),
+            Some(EmailError::IoError(error)) if error.kind() == IoErrorKind::ConnectionRefused
+        ));
+
+        let personal = || {
+            EmailError::PersonalEmailFailed(
+                "spouse@example.com".to_string(),
+                Box::new(EmailError::IoError(IoError::from(IoErrorKind::NotFound))),
+            )
+        };
+        let errors = vec![EmailError::Several(vec![personal(), personal()]).into()];
+        assert!(email_error(errors).is_none());
+        let errors = vec![EmailError::Several(vec![
+            personal(),
+            EmailError::IoError(IoError::from(IoErrorKind::ConnectionRefused)),
+        ])
+        .into()];
+        assert!(email_error(errors).is_some());
+    }
+}
@@ -0,0 +1,282 @@
+//! Signal messenger integration for the Dead Man's Switch.
+//!
+//! Talks to a running [signal-cli-rest-api](https://github.com/bbernhard/signal-cli-rest-api)
+//! to send the switch's messages over Signal, see [`SignalChannel`],
+//! and to let the owner check in by sending `checkin` from their number,
+//! see [`spawn_check_ins`].
+
+use std::io;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::config::Config;
+use crate::notify::{ChannelError, NotificationChannel, SwitchEvent};
+
+/// Interval between checks for incoming check-in messages.
+const CHECK_IN_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The message the owner sends to check in.
+const CHECK_IN_MESSAGE: &str = "checkin";
+
+/// Signal errors.
+#[derive(Error, Debug)]
+pub enum SignalError {
+    /// Error when calling the signal-cli REST API.
+    #[error(transparent)]
+    HttpError(#[from] Box<ureq::Error>),
+    /// Error when reading the response of the signal-cli REST API.
+    #[error(transparent)]
+    IoError(#[from] io::Error),
+}
+
+/// Signal configuration.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SignalConfig {
+    /// URL of the signal-cli REST API, e.g. `http://localhost:8080`.
+    pub url: String,
+    /// The number registered with signal-cli that sends the messages.
+    pub number: String,
+    /// The numbers that receive the messages.
+    pub recipients: Vec<String>,
+    /// The owner's number.
+    ///
+    /// Only `checkin` messages from this number check in.
+    /// If unset, checking in over Signal is disabled.
+    #[serde(default)]
+    pub owner: Option<String>,
+}
+
+/// A message to send with the signal-cli REST API.
+#[derive(Serialize)]
+struct SendRequest<'a> {
+    message: &'a str,
+    number: &'a str,
+    recipients: &'a [String],
+}
+
+/// A message received by the signal-cli REST API.
+#[derive(Deserialize, Debug)]
+struct Received {
+    envelope: Envelope,
+}
+
+/// The envelope of a received message.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Envelope {
+    source_number: Option<String>,
+    data_message: Option<DataMessage>,
+}
+
+/// The content of a received message.
+#[derive(Deserialize, Debug)]
+struct DataMessage {
+    message: Option<String>,
+}
+
+impl SignalConfig {
+    /// Check that the signal-cli REST API is reachable.
+    ///
+    /// ## Errors
+    ///
+    /// - If the request fails or the server responds with an error status.
+    pub fn about(&self) -> Result<(), SignalError> {
+        ureq::get(&format!("{}/v1/about", self.url))
+            .call()
+            .map_err(Box::new)?;
+        Ok(())
+    }
+
+    /// Send a message to the recipients.
+    ///
+    /// ## Errors
+    ///
+    /// - If the request fails or the server responds with an error status.
+    pub fn send(&self, message: &str) -> Result<(), SignalError> {
+        ureq::post(&format!("{}/v2/send", self.url))
+            .send_json(SendRequest {
+                message,
+                number: &self.number,
+                recipients: &self.recipients,
+            })
+            .map_err(Box::new)?;
+        Ok(())
+    }
+
+    /// Receive the pending messages and check if the owner asked to check in.
+    ///
+    /// ## Errors
+    ///
+    /// - If the request fails or the server responds with an error status.
+    /// - If the response is not the expected JSON.
+    pub fn receive_check_in(&self) -> Result<bool, SignalError> {
+        let received: Vec<Received> =
+            ureq::get(&format!("{}/v1/receive/{}", self.url, self.number))
+                .call()
+                .map_err(Box::new)?
+                .into_json()?;
+        Ok(match &self.owner {
+            Some(owner) => check_in_requested(&received, owner),
+            None => false,
+        })
+    }
+}
+
+/// Check if any of the received messages is a check-in from the owner.
+fn check_in_requested(received: &[Received], owner: &str) -> bool {
+    received.iter().any(|received| {
+        let envelope = &received.envelope;
+        let message = envelope
+            .data_message
+            .as_ref()
+            .and_then(|data| data.message.as_deref());
+        envelope.source_number.as_deref() == Some(owner)
+            && matches!(message, Some(message) if message.trim().eq_ignore_ascii_case(CHECK_IN_MESSAGE))
+    })
+}
+
+/// Spawn the thread that watches for check-ins over Signal,
+/// if Signal is configured with an `owner`.
+///
+/// The returned [`Receiver`] gets a message for every check-in.
+/// Failed requests are ignored and retried on the next poll,
+/// so signal-cli being unavailable doesn't stop the switch.
+pub fn spawn_check_ins(config: &Config) -> Option<Receiver<()>> {
+    let signal = config.signal.clone()?;
+    signal.owner.as_ref()?;
+    let (sender, receiver) = mpsc::channel();
+
+    thread::Builder::new()
+        .name("signal".to_string())
+        .spawn(move || loop {
+            if let Ok(true) = signal.receive_check_in() {
+                if sender.send(()).is_err() {
+                    // Nobody is listening anymore
+                    break;
+                }
+            }
+            thread::sleep(CHECK_IN_POLL_INTERVAL);
+        })
+        .ok()?;
+    Some(receiver)
+}
+
+/// The Signal channel.
+///
+/// Sends the subject and message of the [`SwitchEvent`] to the recipients.
+#[derive(Debug, Clone)]
+pub struct SignalChannel {
+    /// The Signal configuration.
+    config: SignalConfig,
+}
+
+impl SignalChannel {
+    /// Create a new Signal channel.
+    pub fn new(config: SignalConfig) -> Self {
+        SignalChannel { config }
+    }
+}
+
+impl NotificationChannel for SignalChannel {
+    fn name(&self) -> &str {
+        "signal"
+    }
+
+    fn send(&self, event: &SwitchEvent) -> Result<(), ChannelError> {
+        let message = format!("{}\n\n{}", event.subject, event.message);
+        self.config.send(&message)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Email;
+    use crate::test_util::serve_once;
+
+    fn get_test_signal_config(url: String) -> SignalConfig {
+        SignalConfig {
+            url,
+            number: "+15550000000".to_string(),
+            recipients: vec!["+15551111111".to_string()],
+            owner: Some("+15552222222".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_signal_channel_send() {
+        let (url, server) = serve_once("{}");
+        let channel = SignalChannel::new(get_test_signal_config(url));
+        let event = Config::default().event(Email::DeadMan);
+
+        channel.send(&event).unwrap();
+        let (request_line, body) = server.join().unwrap();
+        assert!(request_line.starts_with("POST /v2/send"));
+
+        let body: ureq::serde_json::Value = ureq::serde_json::from_str(&body).unwrap();
+        assert_eq!(body["number"], "+15550000000");
+        assert_eq!(body["recipients"][0], "+15551111111");
+        assert_eq!(
+            body["message"],
+            format!("{}\n\n{}", event.subject, event.message)
+        );
+    }
+
+    #[test]
+    fn test_receive_check_in() {
+        let (url, server) = serve_once(
+            r#"[
+                {"envelope": {"sourceNumber": "+15553333333", "dataMessage": {"message": "checkin"}}},
+                {"envelope": {"sourceNumber": "+15552222222", "dataMessage": {"message": " CheckIn "}}}
+            ]"#,
+        );
+        let signal = get_test_signal_config(url);
+
+        assert!(signal.receive_check_in().unwrap());
+        let (request_line, _) = server.join().unwrap();
+        assert!(request_line.starts_with("GET /v1/receive/+15550000000"));
+    }
+
+    #[test]
+    fn test_check_in_only_from_owner() {
+        let received: Vec<Received> = ureq::serde_json::from_str(
+            r#"[
+                {"envelope": {"sourceNumber": "+15553333333", "dataMessage": {"message": "checkin"}}},
+                {"envelope": {"sourceNumber": "+15552222222", "dataMessage": {"message": "hello"}}},
+                {"envelope": {"sourceNumber": "+15552222222"}}
+            ]"#,
+        )
+        .unwrap();
+        assert!(!check_in_requested(&received, "+15552222222"));
+    }
+
+    #[test]
+    fn test_unavailable() {
+        // Nothing listens on the discard port
+        let signal = get_test_signal_config("http://127.0.0.1:9".to_string());
+        assert!(signal.about().is_err());
+        assert!(signal.receive_check_in().is_err());
+    }
+
+    #[test]
+    fn test_config_round_trip() {
+        let config = Config {
+            signal: Some(get_test_signal_config("http://localhost:8080".to_string())),
+            ..Config::default()
+        };
+        let toml = toml::to_string(&config).unwrap();
+        assert_eq!(toml::from_str::<Config>(&toml).unwrap(), config);
+    }
+
+    #[test]
+    fn test_spawn_check_ins_without_signal() {
+        let config = Config::default();
+        assert!(spawn_check_ins(&config).is_none());
+    }
+}
@@ -0,0 +1,137 @@
+//! Telegram notifications of the Dead Man's Switch.
+//!
+//! If `telegram_bot_token` and `telegram_chat_id` are set,
+//! the reminder, warning and dead man's messages are also sent to that chat
+//! with the Telegram Bot API, see [`TelegramChannel`].
+
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::config::Email;
+use crate::notify::{ChannelError, NotificationChannel, SwitchEvent};
+
+/// URL of the Telegram Bot API.
+const TELEGRAM_API_URL: &str = "https://api.telegram.org";
+
+/// Telegram errors.
+#[derive(Error, Debug)]
+pub enum TelegramError {
+    /// Error when calling the Telegram Bot API.
+    #[error(transparent)]
+    HttpError(#[from] Box<ureq::Error>),
+}
+
+/// A message to send with the Telegram Bot API.
+#[derive(Serialize)]
+struct SendMessage<'a> {
+    chat_id: &'a str,
+    text: &'a str,
+}
+
+/// The Telegram channel.
+///
+/// Sends the subject and message of the reminder, warning and dead man's
+/// [`SwitchEvent`]s to the chat, other events are ignored.
+#[derive(Debug, Clone)]
+pub struct TelegramChannel {
+    /// URL of the Telegram Bot API.
+    api_url: String,
+    /// The token of the bot that sends the messages.
+    bot_token: String,
+    /// The chat that receives the messages.
+    chat_id: String,
+}
+
+impl TelegramChannel {
+    /// Create a new Telegram channel.
+    pub fn new(bot_token: String, chat_id: String) -> Self {
+        TelegramChannel::with_api_url(TELEGRAM_API_URL.to_string(), bot_token, chat_id)
+    }
+
+    /// Create a new Telegram channel talking to the given Bot API.
+    fn with_api_url(api_url: String, bot_token: String, chat_id: String) -> Self {
+        TelegramChannel {
+            api_url,
+            bot_token,
+            chat_id,
+        }
+    }
+
+    /// Send a message to the chat.
+    ///
+    /// ## Errors
+    ///
+    /// - If the request fails or the server responds with an error status.
+    pub fn send_message(&self, text: &str) -> Result<(), TelegramError> {
+        ureq::post(&format!(
+            "{}/bot{}/sendMessage",
+            self.api_url, self.bot_token
+        ))
+        .send_json(SendMessage {
+            chat_id: &self.chat_id,
+            text,
+        })
+        .map_err(Box::new)?;
+        Ok(())
+    }
+}
+
+impl NotificationChannel for TelegramChannel {
+    fn name(&self) -> &str {
+        "telegram"
+    }
+
+    fn send(&self, event: &SwitchEvent) -> Result<(), ChannelError> {
+        if !matches!(
+            event.kind,
+            Email::Reminder | Email::Warning | Email::Escalation(_) | Email::DeadMan
+        ) {
+            return Ok(());
+        }
+        let text = format!("{}\n\n{}", event.subject, event.message);
+        self.send_message(&text)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::test_util::serve_once;
+
+    #[test]
+    fn test_telegram_channel_send() {
+        let (url, server) = serve_once(r#"{"ok":true}"#);
+        let channel = TelegramChannel::with_api_url(url, "123:abc".to_string(), "42".to_string());
+        let event = Config::default().event(Email::DeadMan);
+
+        channel.send(&event).unwrap();
+        let (request_line, body) = server.join().unwrap();
+        assert!(request_line.starts_with("POST /bot123:abc/sendMessage "));
+        let body: ureq::serde_json::Value = ureq::serde_json::from_str(&body).unwrap();
+        assert_eq!(body["chat_id"], "42");
+        assert_eq!(
+            body["text"],
+            format!("{}\n\n{}", event.subject, event.message).as_str()
+        );
+    }
+
+    #[test]
+    fn test_telegram_skips_other_events() {
+        // Nothing listens on the discard port, so sending would fail
+        let channel = TelegramChannel::with_api_url(
+            "http://127.0.0.1:9".to_string(),
+            "123:abc".to_string(),
+            "42".to_string(),
+        );
+        let event = Config::default().event(Email::Test);
+        assert!(channel.send(&event).is_ok());
+
+        let event = Config::default().event(Email::Warning);
+        assert!(matches!(
+            channel.send(&event),
+            Err(ChannelError::TelegramError(_))
+        ));
+    }
+}
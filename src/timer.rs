@@ -7,21 +7,184 @@
 //! 1. The [`TimerType::Warning`] timer that emits a warning to the user's
 //!    configured `From` email address upon expiration.
 //! 1. The [`TimerType::DeadMan`] timer that will trigger the message and optional
-//!   attachment to the user's configured `To` email address upon expiration.
+//!    attachment to the user's configured `To` email address upon expiration.
+//!
+//! If the config has [`escalations`](Config::escalations), a
+//! [`TimerType::Escalation`] timer for each of them runs in between,
+//! emitting its email upon expiration.
+//!
+//! Before the switch is armed, by the first check-in, the timer rests in
+//! [`TimerType::Disabled`] and never expires.
+//!
+//! Timers measure elapsed time against a [`ClockSource`],
+//! and can be [`paused`](Timer::pause), e.g. for a planned vacation.
+//!
+//! Once the switch [`fire`](Timer::fire)s, the dead man's email is resent
+//! at the [`dead_man_resends`](Config::dead_man_resends) intervals until a
+//! recipient [`acknowledge`](Timer::acknowledge)s it.
 
-use std::time::{Duration, Instant};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use chrono::Duration as ChronoDuration;
+use serde::{Deserialize, Serialize};
+
+use crate::api::{constant_time_eq, ACKNOWLEDGE_PATH};
+use crate::config::{write_atomically, Config, ConfigError, Email};
+use crate::i18n::{Language, Messages, TimeUnit};
+
+/// The clock used to measure elapsed time.
+///
+/// [`Instant`] may pause while the system is asleep or suspended on some
+/// platforms, which would delay the switch far past the configured deadline.
+/// [`SystemTime`] keeps advancing during sleep, so it is the default.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ClockSource {
+    /// Monotonic clock ([`Instant`]).
+    Monotonic,
+    /// Wall clock ([`SystemTime`]).
+    #[default]
+    WallClock,
+}
 
 /// The timer enum.
 ///
 /// See [`timer`](crate::timer) module for more information.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
 pub enum TimerType {
     /// The warning timer.
     Warning,
+    /// An escalation timer, see [`Config::escalations`].
+    Escalation,
     /// Dead Man's Switch timer.
     DeadMan,
+    /// Not armed: nothing counts down until the switch is armed by a
+    /// check-in.
+    Disabled,
+}
+
+/// Where a check-in came from.
+///
+/// Recorded with every check-in, so that unexpected check-ins are visible
+/// in the [`Timer::check_ins`] history.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckInSource {
+    /// Checked in from the TUI.
+    Tui,
+    /// Checked in from the command line.
+    Cli,
+    /// Checked in with a Signal message.
+    Signal,
+    /// Checked in with the HTTP API.
+    Api,
+}
+
+/// A recorded check-in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CheckIn {
+    /// Where the check-in came from.
+    pub source: CheckInSource,
+    /// When the check-in happened.
+    pub at: SystemTime,
+}
+
+/// The resends of the dead man's email once the switch fired,
+/// see [`Config::dead_man_resends`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Resends {
+    /// The secret token of the acknowledgment link.
+    pub token: String,
+    /// How many times the dead man's email was resent.
+    pub sent: usize,
+    /// When the dead man's email was last sent, in seconds since the Unix
+    /// epoch.
+    pub last_sent: u64,
+    /// Whether a recipient acknowledged the dead man's email.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub acknowledged: bool,
+}
+
+/// The persisted state of a [`Timer`].
+///
+/// The deadline is stored as an absolute wall-clock time, so the countdown
+/// keeps running while the switch is not.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TimerState {
+    /// The timer type.
+    pub timer_type: TimerType,
+    /// The index of the escalation stage, if an escalation timer.
+    #[serde(default)]
+    pub stage: usize,
+    /// The deadline in seconds since the Unix epoch.
+    ///
+    /// While paused, the deadline as of the pause.
+    pub deadline: u64,
+    /// The remaining seconds, if the countdown is paused.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paused_remaining: Option<u64>,
+    /// The last check-in in seconds since the Unix epoch, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_check_in: Option<u64>,
+    /// The seconds the current stage was [`extend`](Timer::extend)ed by,
+    /// if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extension: Option<u64>,
+    /// Whether the reminder of the current warning period was sent.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub reminder_sent: bool,
+    /// The resends of the dead man's email, once the switch fired.
+    ///
+    /// Must be the last field, since TOML tables come after values.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resends: Option<Resends>,
+}
+
+/// What a [`Timer`] shows at a moment, see [`Timer::snapshot`].
+///
+/// The TUI and the metrics display the timer from it, so they agree,
+/// and it serializes for other frontends,
+/// with the [`TimerType`] in `snake_case`, e.g. `"dead_man"`.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct TimerSnapshot {
+    /// The timer type.
+    pub timer_type: TimerType,
+    /// The remaining seconds of the current timer.
+    pub remaining_seconds: u64,
+    /// The remaining time as a percentage of the current timer.
+    pub remaining_percent: u16,
+    /// Whether the countdown is paused.
+    pub paused: bool,
+    /// The remaining time, in the language of the config.
+    pub label: String,
+    /// When the dead man's email is due, in seconds since the Unix epoch,
+    /// if armed.
+    pub deadline: Option<u64>,
+}
+
+/// A paused countdown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Pause {
+    /// The elapsed time, frozen until resumed.
+    elapsed: Duration,
+    /// When the countdown was paused.
+    at: SystemTime,
+}
+
+/// Maximum number of check-ins kept in the [`Timer::check_ins`] history.
+const CHECK_IN_HISTORY_LEN: usize = 100;
+
+/// The longest single [`Timer::extend`], about ten years.
+pub const MAX_EXTENSION: Duration = Duration::from_secs(10 * 365 * 24 * 60 * 60);
+
+/// The extension of `seconds`, if positive and at most [`MAX_EXTENSION`].
+pub fn extension(seconds: u64) -> Option<Duration> {
+    (seconds > 0 && seconds <= MAX_EXTENSION.as_secs()).then_some(Duration::from_secs(seconds))
 }
 
 /// The timer struct.
@@ -31,85 +194,632 @@ pub enum TimerType {
 pub struct Timer {
     /// The timer type.
     timer_type: TimerType,
-    /// The start time.
+    /// The index of the escalation stage, if an escalation timer.
+    stage: usize,
+    /// The clock used to measure elapsed time.
+    clock: ClockSource,
+    /// The start time on the monotonic clock.
     start: Instant,
-    /// The duration.
+    /// The start time on the wall clock.
+    start_wall: SystemTime,
+    /// The duration, including the extension.
     duration: Duration,
+    /// The time the current stage was [`extend`](Timer::extend)ed by.
+    extension: Duration,
+    /// Whether the reminder of the current warning period was sent,
+    /// see [`Config::reminder_before_secs`].
+    reminder_sent: bool,
+    /// The most recent check-ins, oldest first.
+    check_ins: Vec<CheckIn>,
+    /// When the user last checked in, kept across restarts.
+    last_check_in: Option<SystemTime>,
+    /// The pause, if the countdown is paused.
+    pause: Option<Pause>,
+    /// The resends of the dead man's email, once the switch fired.
+    resends: Option<Resends>,
 }
 
 impl Timer {
-    /// Create a new timer.
+    /// Create a new timer using the default [`ClockSource`].
     pub fn new(timer_type: TimerType, duration: Duration) -> Self {
+        Timer::with_clock(timer_type, duration, ClockSource::default())
+    }
+
+    /// Create a new timer measuring elapsed time with the given [`ClockSource`].
+    pub fn with_clock(timer_type: TimerType, duration: Duration, clock: ClockSource) -> Self {
         Timer {
             timer_type,
+            stage: 0,
+            clock,
             start: Instant::now(),
+            start_wall: SystemTime::now(),
             duration,
+            extension: Duration::ZERO,
+            reminder_sent: false,
+            check_ins: Vec::new(),
+            last_check_in: None,
+            pause: None,
+            resends: None,
         }
     }
 
+    /// Create a timer that isn't armed yet, see [`TimerType::Disabled`].
+    pub fn unarmed(clock: ClockSource) -> Self {
+        Timer::with_clock(TimerType::Disabled, Duration::ZERO, clock)
+    }
+
+    /// Create a new timer that started at `start`.
+    ///
+    /// Lets tests fabricate elapsed time instead of sleeping.
+    /// The wall-clock start is set to match, so the timer behaves the same
+    /// with either [`ClockSource`].
+    #[cfg(test)]
+    pub(crate) fn with_start(timer_type: TimerType, duration: Duration, start: Instant) -> Self {
+        let mut timer = Timer::new(timer_type, duration);
+        let elapsed = Instant::now().saturating_duration_since(start);
+        timer.start = start;
+        timer.start_wall = SystemTime::now() - elapsed;
+        timer
+    }
+
+    /// Restore a timer from its persisted [`TimerState`].
+    ///
+    /// The duration comes from the config according to the timer type,
+    /// and the remaining time from the persisted deadline,
+    /// or from the remaining time of a paused countdown.
+    ///
+    /// An escalation stage no longer in the config resumes as the
+    /// dead man's timer.
+    pub fn from_state(state: TimerState, config: &Config) -> Self {
+        let escalation = config.escalations.get(state.stage);
+        let (timer_type, duration) = match (state.timer_type, escalation) {
+            (TimerType::Warning, _) => (TimerType::Warning, config.timer_warning),
+            (TimerType::Escalation, Some(escalation)) => (TimerType::Escalation, escalation.timer),
+            (TimerType::Escalation, None) | (TimerType::DeadMan, _) => {
+                (TimerType::DeadMan, config.timer_dead_man)
+            }
+            (TimerType::Disabled, _) => (TimerType::Disabled, 0),
+        };
+        let extension = Duration::from_secs(state.extension.unwrap_or_default());
+        let duration = Duration::from_secs(duration).saturating_add(extension);
+        let mut timer = Timer::with_clock(timer_type, duration, config.clock);
+        timer.extension = extension;
+        timer.reminder_sent = state.reminder_sent;
+        if timer_type == TimerType::Escalation {
+            timer.stage = state.stage;
+        }
+        if timer_type == TimerType::DeadMan {
+            timer.resends = state.resends;
+        }
+        timer.last_check_in = state
+            .last_check_in
+            .and_then(|last_check_in| UNIX_EPOCH.checked_add(Duration::from_secs(last_check_in)));
+
+        // A deadline out of range restarts the stage
+        let deadline = match UNIX_EPOCH.checked_add(Duration::from_secs(state.deadline)) {
+            Some(deadline) => deadline,
+            None => return timer,
+        };
+        if let Some(remaining) = state.paused_remaining {
+            let remaining = Duration::from_secs(remaining);
+            timer.pause = Some(Pause {
+                elapsed: duration.saturating_sub(remaining),
+                at: deadline.checked_sub(remaining).unwrap_or(deadline),
+            });
+            return timer;
+        }
+        let remaining = deadline
+            .duration_since(SystemTime::now())
+            .unwrap_or_default();
+        // A deadline already in the past leaves the timer expired
+        timer.set_elapsed(duration.saturating_sub(remaining));
+        timer
+    }
+
+    /// Get a [`TimerSnapshot`] of what the timer shows now.
+    pub fn snapshot(&self, config: &Config) -> TimerSnapshot {
+        TimerSnapshot {
+            timer_type: self.timer_type,
+            remaining_seconds: self.remaining_seconds(),
+            remaining_percent: self.remaining_percent(),
+            paused: self.is_paused(),
+            label: self.label(config.language),
+            deadline: self.deadline(config).map(unix_seconds),
+        }
+    }
+
+    /// Get the persistable [`TimerState`] of the timer.
+    ///
+    /// The state of a paused timer doesn't change until it is resumed.
+    pub fn state(&self) -> TimerState {
+        let (now, paused_remaining) = match self.pause {
+            Some(pause) => (pause.at, Some(self.remaining_seconds())),
+            None => (SystemTime::now(), None),
+        };
+        TimerState {
+            timer_type: self.timer_type,
+            stage: self.stage,
+            // One instant floored once, so the deadline doesn't flip
+            // between seconds as `now` and the remaining time move
+            deadline: now
+                .checked_add(self.remaining())
+                .map_or(u64::MAX, unix_seconds),
+            paused_remaining,
+            last_check_in: self.last_check_in.map(|last_check_in| {
+                last_check_in
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs()
+            }),
+            extension: (!self.extension.is_zero()).then_some(self.extension.as_secs()),
+            reminder_sent: self.reminder_sent,
+            resends: self.resends.clone(),
+        }
+    }
+
+    /// Save the timer state to `path`.
+    ///
+    /// ## Errors
+    ///
+    /// - Fails if the state cannot be serialized or written.
+    pub fn save(&self, path: &Path) -> Result<(), ConfigError> {
+        let state = toml::to_string(&self.state())?;
+        write_atomically(path, state.as_bytes())?;
+        Ok(())
+    }
+
+    /// Load a timer from the state saved at `path`.
+    ///
+    /// Returns `None` if there is no saved state.
+    ///
+    /// ## Errors
+    ///
+    /// - Fails if the state file cannot be read or parsed.
+    pub fn load(path: &Path, config: &Config) -> Result<Option<Self>, ConfigError> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let state: TimerState = toml::from_str(&fs::read_to_string(path)?)?;
+        Ok(Some(Timer::from_state(state, config)))
+    }
+
     /// Get the type of the timer.
     /// Returns [`TimerType`].
     pub fn get_type(&self) -> TimerType {
         self.timer_type
     }
 
-    /// Get the elapsed time.
+    /// Get the index of the escalation stage.
+    ///
+    /// Only meaningful for a [`TimerType::Escalation`] timer.
+    pub fn stage(&self) -> usize {
+        self.stage
+    }
+
+    /// Get the clock used to measure elapsed time.
+    pub fn clock(&self) -> ClockSource {
+        self.clock
+    }
+
+    /// Get the elapsed time according to the timer's [`ClockSource`].
+    ///
+    /// If the wall clock has been set backwards past the start time,
+    /// the elapsed time is zero.
+    /// While paused, the elapsed time is frozen.
     pub fn elapsed(&self) -> Duration {
-        Instant::now().duration_since(self.start)
+        if let Some(pause) = self.pause {
+            return pause.elapsed;
+        }
+        match self.clock {
+            ClockSource::Monotonic => Instant::now().duration_since(self.start),
+            ClockSource::WallClock => SystemTime::now()
+                .duration_since(self.start_wall)
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Restart the timer from now on both clocks.
+    ///
+    /// A paused timer stays paused at the start of the countdown.
+    fn restart(&mut self) {
+        self.start = Instant::now();
+        self.start_wall = SystemTime::now();
+        self.extension = Duration::ZERO;
+        if let Some(pause) = &mut self.pause {
+            pause.elapsed = Duration::ZERO;
+            pause.at = self.start_wall;
+        }
+    }
+
+    /// Set the start on both clocks so that `elapsed` has already elapsed.
+    fn set_elapsed(&mut self, elapsed: Duration) {
+        self.start = Instant::now().checked_sub(elapsed).unwrap_or(self.start);
+        self.start_wall = SystemTime::now()
+            .checked_sub(elapsed)
+            .unwrap_or(self.start_wall);
+    }
+
+    /// Pause the countdown, freezing the elapsed time until
+    /// [`resume`](Timer::resume)d.
+    ///
+    /// A [`TimerType::Disabled`] timer has no countdown to pause.
+    pub fn pause(&mut self) {
+        if self.pause.is_none() && self.timer_type != TimerType::Disabled {
+            self.pause = Some(Pause {
+                elapsed: self.elapsed(),
+                at: SystemTime::now(),
+            });
+        }
+    }
+
+    /// Resume a paused countdown where it was paused.
+    pub fn resume(&mut self) {
+        if let Some(pause) = self.pause.take() {
+            self.set_elapsed(pause.elapsed);
+        }
+    }
+
+    /// Postpone the deadline by `extra` without a full check-in.
+    ///
+    /// The extra time is added to the current stage, from now if it has
+    /// already expired, so the [`remaining_percent`](Timer::remaining_percent)
+    /// stays within `0..=100`.
+    /// A [`TimerType::Disabled`] timer has no deadline to postpone.
+    ///
+    /// The extra time is capped at [`MAX_EXTENSION`].
+    pub fn extend(&mut self, extra: Duration) {
+        if self.timer_type == TimerType::Disabled {
+            return;
+        }
+        let extra = extra.min(MAX_EXTENSION);
+        let elapsed = self.elapsed();
+        let expired_by = elapsed.saturating_sub(self.duration);
+        self.duration = self.duration.max(elapsed).saturating_add(extra);
+        self.extension = self
+            .extension
+            .saturating_add(expired_by)
+            .saturating_add(extra);
+    }
+
+    /// Check if the countdown is paused.
+    pub fn is_paused(&self) -> bool {
+        self.pause.is_some()
+    }
+
+    /// Get the remaining time.
+    ///
+    /// Saturates at zero once the timer has expired.
+    pub fn remaining(&self) -> Duration {
+        self.duration.saturating_sub(self.elapsed())
+    }
+
+    /// Get the remaining time in seconds.
+    ///
+    /// Saturates at `0` once the timer has expired.
+    pub fn remaining_seconds(&self) -> u64 {
+        self.remaining().as_secs()
+    }
+
+    /// Get the elapsed time as a fraction of the duration in `0.0..=1.0`.
+    ///
+    /// A timer with a zero duration is always fully elapsed,
+    /// and a [`TimerType::Disabled`] one never starts.
+    pub fn progress(&self) -> f64 {
+        if self.timer_type == TimerType::Disabled {
+            return 0.0;
+        }
+        if self.duration.is_zero() {
+            return 1.0;
+        }
+        (self.elapsed().as_secs_f64() / self.duration.as_secs_f64()).min(1.0)
+    }
+
+    /// When the dead man's email is due if nobody checks in.
+    ///
+    /// Adds the durations of the stages after the current one, see
+    /// [`Config::escalations`], to the remaining time.
+    /// `None` for a [`TimerType::Disabled`] timer, which never fires.
+    pub fn deadline(&self, config: &Config) -> Option<SystemTime> {
+        let escalations = |from: usize| -> u64 {
+            config
+                .escalations
+                .iter()
+                .skip(from)
+                .map(|escalation| escalation.timer)
+                .sum()
+        };
+        let later = match self.timer_type {
+            TimerType::Warning => escalations(0) + config.timer_dead_man,
+            TimerType::Escalation => escalations(self.stage + 1) + config.timer_dead_man,
+            TimerType::DeadMan => 0,
+            TimerType::Disabled => return None,
+        };
+        SystemTime::now().checked_add(self.remaining().saturating_add(Duration::from_secs(later)))
     }
 
     /// Calculate the remaining time as a percentage
     pub fn remaining_percent(&self) -> u16 {
-        let elapsed = self.start.elapsed().as_secs();
-        let total = self.duration.as_secs();
-        let remaining = if elapsed < total { total - elapsed } else { 0 };
-        (remaining as f64 / total as f64 * 100.0) as u16
+        ((1.0 - self.progress()) * 100.0) as u16
     }
 
-    /// Update label based on the remaining time
-    pub fn label(&self) -> String {
-        let remaining = self.duration - self.start.elapsed();
-        let remaining_chrono =
-            ChronoDuration::try_seconds(remaining.as_secs() as i64).expect("Invalid duration");
-        format_duration(remaining_chrono)
+    /// Update label based on the remaining time, in the given [`Language`]
+    pub fn label(&self, language: Language) -> String {
+        if self.timer_type == TimerType::Disabled {
+            return language.messages().not_armed.to_string();
+        }
+        let remaining = self.remaining();
+        let remaining_chrono = i64::try_from(remaining.as_secs())
+            .ok()
+            .and_then(ChronoDuration::try_seconds)
+            .unwrap_or(ChronoDuration::MAX);
+        let label = format_duration(remaining_chrono, language);
+        if self.is_paused() {
+            format!("{} ({label})", language.messages().paused)
+        } else {
+            label
+        }
+    }
+
+    /// The title of the timer, according to its type.
+    pub fn title(&self, messages: &Messages) -> String {
+        if self.resends.is_some() {
+            return messages.resending.to_string();
+        }
+        match self.get_type() {
+            TimerType::Warning => messages.warning.to_string(),
+            TimerType::Escalation => format!("{} {}", messages.escalation, self.stage() + 1),
+            TimerType::DeadMan => messages.dead_man.to_string(),
+            TimerType::Disabled => messages.not_armed.to_string(),
+        }
     }
 
     /// Update the timer logic for switching from [`TimerType::Warning`] to
     /// [`TimerType::DeadMan`].
+    ///
+    /// Skips any escalations, see [`Timer::tick`] to walk through them.
     pub fn update(&mut self, elapsed: Duration, dead_man_duration: u64) {
         if self.timer_type == TimerType::Warning && elapsed >= self.duration {
             self.timer_type = TimerType::DeadMan;
             // Reset the start time for the DeadMan timer
-            self.start = Instant::now();
+            self.restart();
             self.duration = Duration::from_secs(dead_man_duration);
         }
     }
 
     /// Check if the timer has expired.
+    ///
+    /// A [`TimerType::Disabled`] timer never expires.
     pub fn expired(&self) -> bool {
-        self.start.elapsed() >= self.duration
+        self.timer_type != TimerType::Disabled && self.elapsed() >= self.duration
+    }
+
+    /// Advance the timer and get the email to send now, if any.
+    ///
+    /// Once the warning timer expires this returns [`Email::Warning`] and
+    /// advances the timer to the first [`TimerType::Escalation`], if any,
+    /// or to [`TimerType::DeadMan`],
+    /// so the warning is sent exactly once per warning period,
+    /// until a check-in starts a new one.
+    /// Each escalation timer likewise returns its [`Email::Escalation`]
+    /// and advances to the next stage.
+    /// Once the dead man's timer expires this returns [`Email::DeadMan`],
+    /// and once it [`fire`](Timer::fire)d, again whenever a resend is due.
+    /// Within [`Config::reminder_before_secs`] of the end of the warning
+    /// period this returns [`Email::Reminder`], once per warning period.
+    ///
+    /// A disabled switch, see [`Config::enabled`], is left untouched and
+    /// never returns an email.
+    pub fn tick(&mut self, config: &Config) -> Option<Email> {
+        if !config.enabled {
+            return None;
+        }
+        if let Some(resends) = &mut self.resends {
+            let now = unix_seconds(SystemTime::now());
+            let due = config
+                .dead_man_resends
+                .get(resends.sent)
+                .map_or(false, |interval| {
+                    now >= resends.last_sent.saturating_add(*interval)
+                });
+            if resends.acknowledged || !due {
+                return None;
+            }
+            resends.sent += 1;
+            resends.last_sent = now;
+            return Some(Email::DeadMan);
+        }
+        if !self.expired() {
+            let reminder_due = self.timer_type == TimerType::Warning
+                && !self.reminder_sent
+                && config.reminder_before_secs.map_or(false, |before| {
+                    self.remaining() <= Duration::from_secs(before)
+                });
+            if reminder_due {
+                self.reminder_sent = true;
+                return Some(Email::Reminder);
+            }
+            return None;
+        }
+        match self.timer_type {
+            TimerType::Warning => {
+                self.escalate(0, config);
+                Some(Email::Warning)
+            }
+            TimerType::Escalation => {
+                let stage = self.stage;
+                self.escalate(stage + 1, config);
+                Some(Email::Escalation(stage))
+            }
+            TimerType::DeadMan => Some(Email::DeadMan),
+            TimerType::Disabled => None,
+        }
+    }
+
+    /// Restart the timer as the escalation `stage`,
+    /// or as the dead man's timer past the last escalation.
+    fn escalate(&mut self, stage: usize, config: &Config) {
+        match config.escalations.get(stage) {
+            Some(escalation) => {
+                self.timer_type = TimerType::Escalation;
+                self.stage = stage;
+                self.duration = Duration::from_secs(escalation.timer);
+            }
+            None => {
+                self.timer_type = TimerType::DeadMan;
+                self.stage = 0;
+                self.duration = Duration::from_secs(config.timer_dead_man);
+            }
+        }
+        self.restart();
+    }
+
+    /// Check in: [`reset`](Timer::reset) the timer and record where the
+    /// check-in came from.
+    pub fn check_in(&mut self, config: &crate::config::Config, source: CheckInSource) {
+        self.reset(config);
+        let now = SystemTime::now();
+        if self.check_ins.len() == CHECK_IN_HISTORY_LEN {
+            self.check_ins.remove(0);
+        }
+        self.check_ins.push(CheckIn { source, at: now });
+        self.last_check_in = Some(now);
+    }
+
+    /// Get the most recent check-ins, oldest first.
+    pub fn check_ins(&self) -> &[CheckIn] {
+        &self.check_ins
+    }
+
+    /// Get when the user last checked in, if ever.
+    ///
+    /// Unlike the [`check_ins`](Timer::check_ins) history,
+    /// it is restored from the [`TimerState`].
+    pub fn last_check_in(&self) -> Option<SystemTime> {
+        self.last_check_in
     }
 
     /// Reset the timer and promotes the timer type from [`TimerType::DeadMan`]
-    /// to [`TimerType::Warning`], if applicable.
+    /// or [`TimerType::Escalation`] to [`TimerType::Warning`], if applicable.
+    /// A [`TimerType::Disabled`] timer is armed the same way.
     ///
     /// This is called when the user checks in.
     pub fn reset(&mut self, config: &crate::config::Config) {
         match self.get_type() {
             TimerType::Warning => {
-                self.start = Instant::now();
+                self.restart();
+                self.duration = Duration::from_secs(config.timer_warning);
             }
-            TimerType::DeadMan => {
+            TimerType::Escalation | TimerType::DeadMan | TimerType::Disabled => {
                 self.timer_type = TimerType::Warning;
-                self.start = Instant::now();
+                self.stage = 0;
+                self.restart();
                 self.duration = Duration::from_secs(config.timer_warning);
             }
         }
+        self.reminder_sent = false;
+        self.resends = None;
+    }
+
+    /// Fire the switch, as the first dead man's email is about to be sent.
+    ///
+    /// Starts resending it, with a new acknowledgment token,
+    /// if [`Config::dead_man_resends`] are configured.
+    /// A check-in stops the resends.
+    ///
+    /// ## Errors
+    ///
+    /// - If no random token can be generated.
+    pub fn fire(&mut self, config: &Config) -> io::Result<()> {
+        if self.resends.is_none() && !config.dead_man_resends.is_empty() {
+            self.resends = Some(Resends {
+                token: new_token()?,
+                sent: 0,
+                last_sent: unix_seconds(SystemTime::now()),
+                acknowledged: false,
+            });
+        }
+        Ok(())
+    }
+
+    /// Check if the switch [`fire`](Timer::fire)d with resends configured.
+    pub fn fired(&self) -> bool {
+        self.resends.is_some()
+    }
+
+    /// Check if the dead man's email is still resent: the switch
+    /// [`fire`](Timer::fire)d, nobody acknowledged it and resends are left.
+    pub fn resending(&self, config: &Config) -> bool {
+        matches!(
+            &self.resends,
+            Some(resends) if !resends.acknowledged && resends.sent < config.dead_man_resends.len()
+        )
+    }
+
+    /// The link to acknowledge the dead man's email with, while it is
+    /// resent, on the [`Config::public_url`].
+    pub fn acknowledge_url(&self, config: &Config) -> Option<String> {
+        let resends = self.resends.as_ref()?;
+        let public_url = config.public_url.as_deref()?;
+        Some(format!(
+            "{}{ACKNOWLEDGE_PATH}{}",
+            public_url.trim_end_matches('/'),
+            resends.token
+        ))
+    }
+
+    /// Acknowledge the dead man's email with the `token` of its link,
+    /// stopping the resends.
+    ///
+    /// Returns whether the token is right.
+    pub fn acknowledge(&mut self, token: &str) -> bool {
+        match &mut self.resends {
+            Some(resends) if constant_time_eq(token.as_bytes(), resends.token.as_bytes()) => {
+                resends.acknowledged = true;
+                true
+            }
+            _ => false,
+        }
     }
 }
 
+/// A random hex token of 128 bits, for the acknowledgment link.
+fn new_token() -> io::Result<String> {
+    let mut bytes = [0; 16];
+    getrandom::getrandom(&mut bytes)?;
+    Ok(bytes.iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+/// Seconds since the Unix epoch.
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
 /// Formats a duration into a human-readable string adjusting the resolution based on the duration.
-fn format_duration(duration: ChronoDuration) -> String {
+///
+/// Durations under a minute, negative ones included, render as
+/// "less than a minute", longer ones as [`format_duration_exact`].
+pub(crate) fn format_duration(duration: ChronoDuration, language: Language) -> String {
+    if duration < ChronoDuration::minutes(1) {
+        language.messages().less_than_a_minute.to_string()
+    } else {
+        format_duration_exact(duration, language)
+    }
+}
+
+/// Formats a duration into a human-readable string down to the second,
+/// e.g. of a configured timer.
+///
+/// The units are pluralized according to the given [`Language`].
+///
+/// ## Notes
+///
+/// Negative durations, e.g. of a timer queried past its expiry,
+/// are clamped to zero and render as "0 seconds".
+/// Sub-second remainders are truncated, like [`Timer::remaining_seconds`].
+pub(crate) fn format_duration_exact(duration: ChronoDuration, language: Language) -> String {
+    let duration = duration.max(ChronoDuration::zero());
     let days = duration.num_days();
     let hours = duration.num_hours() % 24;
     let minutes = duration.num_minutes() % 60;
@@ -118,16 +828,16 @@ fn format_duration(duration: ChronoDuration) -> String {
     let mut parts = vec![];
 
     if days > 0 {
-        parts.push(format!("{} day(s)", days));
+        parts.push(language.unit(TimeUnit::Day, days));
     }
     if hours > 0 {
-        parts.push(format!("{} hour(s)", hours));
+        parts.push(language.unit(TimeUnit::Hour, hours));
     }
     if minutes > 0 {
-        parts.push(format!("{} minute(s)", minutes));
+        parts.push(language.unit(TimeUnit::Minute, minutes));
     }
     if seconds > 0 || parts.is_empty() {
-        parts.push(format!("{} second(s)", seconds + 1));
+        parts.push(language.unit(TimeUnit::Second, seconds));
     }
 
     parts.join(", ")
@@ -137,7 +847,12 @@ fn format_duration(duration: ChronoDuration) -> String {
 mod tests {
     use super::*;
     use crate::config::load_or_initialize_config;
-    use std::thread::sleep;
+    use proptest::prelude::*;
+
+    /// An [`Instant`] `secs` seconds in the past.
+    fn secs_ago(secs: u64) -> Instant {
+        Instant::now() - Duration::from_secs(secs)
+    }
 
     #[test]
     fn timer_creation() {
@@ -162,25 +877,352 @@ mod tests {
         assert!(!timer.expired());
     }
 
+    #[test]
+    fn remaining_seconds_saturates_at_zero() {
+        let timer = Timer::new(TimerType::Warning, Duration::from_secs(60));
+        assert!(timer.remaining_seconds() <= 60);
+
+        let timer = Timer::with_start(TimerType::Warning, Duration::from_secs(1), secs_ago(2));
+        assert_eq!(timer.remaining_seconds(), 0);
+    }
+
+    #[test]
+    fn snapshot_serializes_the_timer_type_in_snake_case() {
+        let config = Config::default();
+        let timer = Timer::new(TimerType::DeadMan, Duration::from_secs(60));
+        let snapshot = timer.snapshot(&config);
+        assert_eq!(snapshot.timer_type, TimerType::DeadMan);
+        assert_eq!(snapshot.label, timer.label(config.language));
+        assert!(snapshot.deadline.is_some());
+
+        let serialized = toml::to_string(&snapshot).unwrap();
+        assert!(serialized.contains("timer_type = \"dead_man\""));
+        assert!(serialized.contains("paused = false"));
+
+        let unarmed = Timer::unarmed(config.clock).snapshot(&config);
+        assert_eq!(unarmed.deadline, None);
+    }
+
+    #[test]
+    fn label_of_expired_timer() {
+        let timer = Timer::with_start(TimerType::DeadMan, Duration::from_secs(1), secs_ago(2));
+        assert_eq!(timer.label(Language::English), "less than a minute");
+    }
+
+    #[test]
+    fn progress_is_clamped() {
+        let mut timer = Timer::with_clock(
+            TimerType::Warning,
+            Duration::from_secs(100),
+            ClockSource::WallClock,
+        );
+        assert!(timer.progress() < 0.1);
+
+        timer.start_wall -= Duration::from_secs(50);
+        assert!((0.5..0.6).contains(&timer.progress()));
+        assert!(timer.remaining() <= Duration::from_secs(50));
+
+        timer.start_wall -= Duration::from_secs(100);
+        assert_eq!(timer.progress(), 1.0);
+        assert_eq!(timer.remaining(), Duration::ZERO);
+    }
+
+    #[test]
+    fn progress_with_zero_duration() {
+        let timer = Timer::new(TimerType::Warning, Duration::ZERO);
+        assert_eq!(timer.progress(), 1.0);
+        assert_eq!(timer.remaining(), Duration::ZERO);
+        assert_eq!(timer.remaining_percent(), 0);
+        assert!(timer.expired());
+    }
+
     #[test]
     fn timer_expiration() {
-        let timer = Timer::new(TimerType::Warning, Duration::from_secs(1));
-        // Directly simulate the passage of time
-        sleep(Duration::from_secs(2));
+        let timer = Timer::with_start(TimerType::Warning, Duration::from_secs(60), secs_ago(59));
+        assert!(!timer.expired());
+        let timer = Timer::with_start(TimerType::Warning, Duration::from_secs(60), secs_ago(61));
+        assert!(timer.expired());
+    }
+
+    #[test]
+    fn tick_when_expired() {
+        let config = Config::default();
+        let mut timer = Timer::with_start(TimerType::Warning, Duration::from_secs(60), secs_ago(2));
+        assert_eq!(timer.tick(&config), None);
+
+        let mut timer = Timer::with_start(TimerType::DeadMan, Duration::from_secs(1), secs_ago(2));
+        assert_eq!(timer.tick(&config), Some(Email::DeadMan));
+    }
+
+    #[test]
+    fn tick_sends_the_warning_once() {
+        let config = Config::default();
+        let mut timer = Timer::with_start(TimerType::Warning, Duration::from_secs(1), secs_ago(2));
+
+        let sent: Vec<_> = (0..10).filter_map(|_| timer.tick(&config)).collect();
+        assert_eq!(sent, vec![Email::Warning]);
+        assert_eq!(timer.get_type(), TimerType::DeadMan);
+
+        // Checking in starts a new warning period
+        timer.check_in(&config, CheckInSource::Tui);
+        assert_eq!(timer.get_type(), TimerType::Warning);
+        assert_eq!(timer.tick(&config), None);
+    }
+
+    #[test]
+    fn deadline_includes_the_later_stages() {
+        let config = Config {
+            timer_dead_man: 100,
+            escalations: vec![crate::config::Escalation {
+                timer: 60,
+                subject: "Urgent".to_string(),
+                message: "Check in now".to_string(),
+                to: None,
+            }],
+            ..Config::default()
+        };
+        let in_secs = |timer: &Timer| {
+            timer
+                .deadline(&config)
+                .unwrap()
+                .duration_since(SystemTime::now())
+                .unwrap()
+                .as_secs_f64()
+                .round() as u64
+        };
+
+        let mut timer = Timer::new(TimerType::Warning, Duration::from_secs(1000));
+        assert_eq!(in_secs(&timer), 1160);
+        timer.escalate(0, &config);
+        assert_eq!(in_secs(&timer), 160);
+        timer.escalate(1, &config);
+        assert_eq!(in_secs(&timer), 100);
+        assert_eq!(Timer::unarmed(config.clock).deadline(&config), None);
+    }
+
+    #[test]
+    fn tick_sends_the_reminder_once() {
+        let config = Config {
+            reminder_before_secs: Some(30),
+            ..Config::default()
+        };
+        let mut timer =
+            Timer::with_start(TimerType::Warning, Duration::from_secs(60), secs_ago(10));
+        assert_eq!(timer.tick(&config), None);
+
+        timer.set_elapsed(Duration::from_secs(40));
+        let sent: Vec<_> = (0..10).filter_map(|_| timer.tick(&config)).collect();
+        assert_eq!(sent, vec![Email::Reminder]);
+
+        // The flag survives a restart
+        let mut resumed = Timer::from_state(timer.state(), &config);
+        assert_eq!(resumed.tick(&config), None);
+
+        // Checking in starts a new warning period with its own reminder
+        timer.check_in(&config, CheckInSource::Tui);
+        timer.set_elapsed(Duration::from_secs(config.timer_warning - 10));
+        assert_eq!(timer.tick(&config), Some(Email::Reminder));
+    }
+
+    #[test]
+    fn resends_until_acknowledged() {
+        let config = Config {
+            dead_man_resends: vec![60, 120],
+            public_url: Some("https://switch.example.com/".to_string()),
+            ..Config::default()
+        };
+        let mut timer =
+            Timer::with_start(TimerType::DeadMan, Duration::from_secs(1), secs_ago(3600));
+        assert_eq!(timer.tick(&config), Some(Email::DeadMan));
+        assert_eq!(timer.acknowledge_url(&config), None);
+
+        timer.fire(&config).unwrap();
+        assert!(timer.fired());
+        assert!(timer.resending(&config));
+        let url = timer.acknowledge_url(&config).unwrap();
+        let token = url
+            .strip_prefix("https://switch.example.com/acknowledge/")
+            .unwrap()
+            .to_string();
+        assert_eq!(token.len(), 32);
+
+        // The resend is due after its interval
+        assert_eq!(timer.tick(&config), None);
+        let resends = timer.resends.as_mut().unwrap();
+        resends.last_sent -= 60;
+        assert_eq!(timer.tick(&config), Some(Email::DeadMan));
+        assert_eq!(timer.tick(&config), None);
+
+        // The state keeps the resends
+        let mut timer = Timer::from_state(timer.state(), &config);
+        assert_eq!(timer.acknowledge_url(&config), Some(url));
+        assert!(!timer.acknowledge("wrong"));
+        assert!(timer.resending(&config));
+        assert!(timer.acknowledge(&token));
+        assert!(!timer.resending(&config));
+        timer.resends.as_mut().unwrap().last_sent -= 120;
+        assert_eq!(timer.tick(&config), None);
+
+        // A check-in stops the resends
+        timer.check_in(&config, CheckInSource::Tui);
+        assert!(!timer.fired());
+    }
+
+    #[test]
+    fn resends_run_out() {
+        let config = Config {
+            dead_man_resends: vec![60],
+            public_url: Some("https://switch.example.com".to_string()),
+            ..Config::default()
+        };
+        let mut timer =
+            Timer::with_start(TimerType::DeadMan, Duration::from_secs(1), secs_ago(3600));
+        timer.fire(&config).unwrap();
+        timer.resends.as_mut().unwrap().last_sent -= 60;
+        assert_eq!(timer.tick(&config), Some(Email::DeadMan));
+        assert!(!timer.resending(&config));
+        assert_eq!(timer.tick(&config), None);
+    }
+
+    #[test]
+    fn disabled_switch_never_fires() {
+        let config = Config {
+            enabled: false,
+            ..Config::default()
+        };
+        let mut timer =
+            Timer::with_start(TimerType::DeadMan, Duration::from_secs(1), secs_ago(3600));
         assert!(timer.expired());
+        assert_eq!(timer.tick(&config), None);
+        assert_eq!(timer.get_type(), TimerType::DeadMan);
+    }
+
+    #[test]
+    fn disabled_timer_never_expires() {
+        let config = Config::default();
+        let mut timer = Timer::with_start(TimerType::Disabled, Duration::ZERO, secs_ago(3600));
+        assert!(!timer.expired());
+        assert_eq!(timer.remaining_percent(), 100);
+        assert_eq!(timer.label(Language::English), "Not armed");
+
+        timer.update(timer.elapsed(), config.timer_dead_man);
+        assert_eq!(timer.get_type(), TimerType::Disabled);
+        assert_eq!(timer.tick(&config), None);
+    }
+
+    #[test]
+    fn reset_arms_disabled_timer() {
+        let config = Config::default();
+        let mut timer = Timer::new(TimerType::Disabled, Duration::ZERO);
+
+        timer.check_in(&config, CheckInSource::Tui);
+        assert_eq!(timer.get_type(), TimerType::Warning);
+        assert_eq!(timer.duration, Duration::from_secs(config.timer_warning));
+        assert!(!timer.expired());
+    }
+
+    #[test]
+    fn disabled_state_round_trips() {
+        let config = Config::default();
+        let timer = Timer::new(TimerType::Disabled, Duration::ZERO);
+        let timer = Timer::from_state(timer.state(), &config);
+        assert_eq!(timer.get_type(), TimerType::Disabled);
+        assert!(!timer.expired());
+    }
+
+    #[test]
+    fn wall_clock_is_the_default() {
+        let timer = Timer::new(TimerType::Warning, Duration::from_secs(60));
+        assert_eq!(timer.clock(), ClockSource::WallClock);
+    }
+
+    #[test]
+    fn wall_clock_elapsed_tracks_system_time() {
+        let mut timer = Timer::with_clock(
+            TimerType::Warning,
+            Duration::from_secs(60),
+            ClockSource::WallClock,
+        );
+        // Simulate the system sleeping for 30 seconds
+        timer.start_wall -= Duration::from_secs(30);
+        assert!(timer.elapsed() >= Duration::from_secs(30));
+        assert!(timer.remaining_percent() <= 50);
+        assert!(!timer.expired());
+
+        // Simulate the system sleeping past the deadline
+        timer.start_wall -= Duration::from_secs(60);
+        assert!(timer.expired());
+        assert_eq!(timer.remaining_percent(), 0);
+    }
+
+    #[test]
+    fn wall_clock_set_backwards_does_not_panic() {
+        let mut timer = Timer::with_clock(
+            TimerType::Warning,
+            Duration::from_secs(60),
+            ClockSource::WallClock,
+        );
+        // Simulate the wall clock being set backwards
+        timer.start_wall += Duration::from_secs(30);
+        assert_eq!(timer.elapsed(), Duration::ZERO);
+        assert!(!timer.expired());
+    }
+
+    #[test]
+    fn monotonic_clock_ignores_wall_clock() {
+        let mut timer = Timer::with_clock(
+            TimerType::Warning,
+            Duration::from_secs(60),
+            ClockSource::Monotonic,
+        );
+        timer.start_wall -= Duration::from_secs(120);
+        assert!(!timer.expired());
     }
 
     #[test]
     fn format_seconds_only() {
         let duration = ChronoDuration::try_seconds(45).unwrap();
-        assert_eq!(format_duration(duration), "46 second(s)");
+        assert_eq!(
+            format_duration_exact(duration, Language::English),
+            "45 seconds"
+        );
+    }
+
+    #[test]
+    fn format_less_than_a_minute() {
+        for seconds in [0, 1, 59, -90] {
+            let duration = ChronoDuration::try_seconds(seconds).unwrap();
+            assert_eq!(
+                format_duration(duration, Language::English),
+                "less than a minute"
+            );
+        }
+        let duration = ChronoDuration::try_seconds(1).unwrap();
+        assert_eq!(
+            format_duration(duration, Language::Portuguese),
+            "menos de um minuto"
+        );
+    }
+
+    #[test]
+    fn format_singular_units() {
+        let second = ChronoDuration::try_seconds(1).unwrap();
+        assert_eq!(format_duration_exact(second, Language::English), "1 second");
+        let minute_and_second = ChronoDuration::try_seconds(61).unwrap();
+        assert_eq!(
+            format_duration(minute_and_second, Language::English),
+            "1 minute, 1 second"
+        );
     }
 
     #[test]
     fn format_minutes_and_seconds() {
         let duration =
             ChronoDuration::try_minutes(5).unwrap() + ChronoDuration::try_seconds(30).unwrap();
-        assert_eq!(format_duration(duration), "5 minute(s), 31 second(s)");
+        assert_eq!(
+            format_duration(duration, Language::English),
+            "5 minutes, 30 seconds"
+        );
     }
 
     #[test]
@@ -189,8 +1231,8 @@ mod tests {
             + ChronoDuration::try_minutes(15).unwrap()
             + ChronoDuration::try_seconds(10).unwrap();
         assert_eq!(
-            format_duration(duration),
-            "2 hour(s), 15 minute(s), 11 second(s)"
+            format_duration(duration, Language::English),
+            "2 hours, 15 minutes, 10 seconds"
         );
     }
 
@@ -200,15 +1242,15 @@ mod tests {
             + ChronoDuration::try_hours(3).unwrap()
             + ChronoDuration::try_minutes(45).unwrap();
         assert_eq!(
-            format_duration(duration),
-            "1 day(s), 3 hour(s), 45 minute(s)"
+            format_duration(duration, Language::English),
+            "1 day, 3 hours, 45 minutes"
         );
     }
 
     #[test]
     fn format_days_only() {
         let duration = ChronoDuration::try_days(4).unwrap();
-        assert_eq!(format_duration(duration), "4 day(s)");
+        assert_eq!(format_duration(duration, Language::English), "4 days");
     }
 
     #[test]
@@ -218,24 +1260,84 @@ mod tests {
             + ChronoDuration::try_minutes(59).unwrap()
             + ChronoDuration::try_seconds(59).unwrap();
         assert_eq!(
-            format_duration(duration),
-            "7 day(s), 23 hour(s), 59 minute(s), 60 second(s)"
+            format_duration(duration, Language::English),
+            "7 days, 23 hours, 59 minutes, 59 seconds"
+        );
+    }
+
+    #[test]
+    fn format_portuguese() {
+        let duration = ChronoDuration::try_days(1).unwrap()
+            + ChronoDuration::try_hours(1).unwrap()
+            + ChronoDuration::try_minutes(2).unwrap();
+        assert_eq!(
+            format_duration(duration, Language::Portuguese),
+            "1 dia, 1 hora, 2 minutos"
         );
     }
 
+    #[test]
+    fn format_zero_and_negative() {
+        assert_eq!(
+            format_duration_exact(ChronoDuration::zero(), Language::English),
+            "0 seconds"
+        );
+        let duration = ChronoDuration::try_seconds(-90).unwrap();
+        assert_eq!(
+            format_duration_exact(duration, Language::English),
+            "0 seconds"
+        );
+    }
+
+    /// Parse a formatted English duration back into seconds.
+    fn parse_seconds(formatted: &str) -> i64 {
+        formatted
+            .split(", ")
+            .map(|part| {
+                let (quantity, unit) = part.split_once(' ').unwrap();
+                let quantity: i64 = quantity.parse().unwrap();
+                let unit_seconds = match unit {
+                    "day" | "days" => 60 * 60 * 24,
+                    "hour" | "hours" => 60 * 60,
+                    "minute" | "minutes" => 60,
+                    "second" | "seconds" => 1,
+                    _ => panic!("unknown unit {unit}"),
+                };
+                quantity * unit_seconds
+            })
+            .sum()
+    }
+
+    proptest! {
+        #[test]
+        fn format_round_trips(seconds in -1_000_000_000i64..1_000_000_000) {
+            let duration = ChronoDuration::try_seconds(seconds).unwrap();
+            let formatted = format_duration_exact(duration, Language::English);
+            prop_assert_eq!(parse_seconds(&formatted), seconds.max(0));
+        }
+
+        #[test]
+        fn format_is_monotonic(a in -1_000_000_000i64..1_000_000_000, b in -1_000_000_000i64..1_000_000_000) {
+            let (shorter, longer) = (a.min(b), a.max(b));
+            let shorter = format_duration_exact(ChronoDuration::try_seconds(shorter).unwrap(), Language::English);
+            let longer = format_duration_exact(ChronoDuration::try_seconds(longer).unwrap(), Language::English);
+            prop_assert!(parse_seconds(&shorter) <= parse_seconds(&longer));
+        }
+    }
+
     #[test]
     fn reset_warning_timer_resets_start_time() {
         let config = load_or_initialize_config().unwrap();
 
-        let mut timer = Timer::new(
+        let original_start = secs_ago(60);
+        let mut timer = Timer::with_start(
             TimerType::Warning,
             Duration::from_secs(config.timer_warning),
+            original_start,
         );
-        let original_start = timer.start;
-        // Simulate time passing
-        sleep(Duration::from_millis(100));
         timer.reset(&config);
         assert!(timer.start > original_start);
+        assert!(timer.elapsed() < Duration::from_secs(60));
         assert_eq!(timer.duration, Duration::from_secs(config.timer_warning));
         assert_eq!(timer.get_type(), TimerType::Warning);
     }
@@ -244,14 +1346,289 @@ mod tests {
     fn reset_dead_man_timer_promotes_to_warning_and_resets() {
         let config = load_or_initialize_config().unwrap();
 
-        let mut timer = Timer::new(
+        let mut timer = Timer::with_start(
             TimerType::DeadMan,
             Duration::from_secs(config.timer_dead_man),
+            secs_ago(60),
         );
-        // Simulate time passing
-        sleep(Duration::from_millis(100));
         timer.reset(&config);
+        assert!(timer.elapsed() < Duration::from_secs(60));
         assert_eq!(timer.get_type(), TimerType::Warning);
         assert_eq!(timer.duration, Duration::from_secs(config.timer_warning));
     }
+
+    #[test]
+    fn check_in_records_source() {
+        let config = load_or_initialize_config().unwrap();
+
+        let mut timer = Timer::new(TimerType::DeadMan, Duration::from_secs(60));
+        assert!(timer.check_ins().is_empty());
+
+        timer.check_in(&config, CheckInSource::Tui);
+        timer.check_in(&config, CheckInSource::Cli);
+        assert_eq!(timer.get_type(), TimerType::Warning);
+        let sources: Vec<_> = timer.check_ins().iter().map(|c| c.source).collect();
+        assert_eq!(sources, vec![CheckInSource::Tui, CheckInSource::Cli]);
+    }
+
+    #[test]
+    fn check_in_history_is_bounded() {
+        let config = load_or_initialize_config().unwrap();
+
+        let mut timer = Timer::new(TimerType::Warning, Duration::from_secs(60));
+        for _ in 0..CHECK_IN_HISTORY_LEN {
+            timer.check_in(&config, CheckInSource::Tui);
+        }
+        timer.check_in(&config, CheckInSource::Cli);
+        assert_eq!(timer.check_ins().len(), CHECK_IN_HISTORY_LEN);
+        assert_eq!(
+            timer.check_ins().last().map(|c| c.source),
+            Some(CheckInSource::Cli)
+        );
+    }
+
+    #[test]
+    fn save_and_load_state() {
+        let config = load_or_initialize_config().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.toml");
+
+        assert!(Timer::load(&path, &config).unwrap().is_none());
+
+        let mut timer = Timer::with_start(
+            TimerType::DeadMan,
+            Duration::from_secs(config.timer_dead_man),
+            secs_ago(60),
+        );
+        timer.save(&path).unwrap();
+        assert!(Timer::load(&path, &config)
+            .unwrap()
+            .unwrap()
+            .last_check_in()
+            .is_none());
+
+        timer.check_in(&config, CheckInSource::Tui);
+        timer.save(&path).unwrap();
+
+        let loaded = Timer::load(&path, &config).unwrap().unwrap();
+        assert_eq!(loaded.get_type(), TimerType::Warning);
+        let last_check_in = |timer: &Timer| {
+            timer
+                .last_check_in()
+                .unwrap()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs()
+        };
+        assert_eq!(last_check_in(&loaded), last_check_in(&timer));
+        let drift = loaded
+            .remaining_seconds()
+            .abs_diff(timer.remaining_seconds());
+        assert!(drift <= 1);
+    }
+
+    #[test]
+    fn state_with_past_deadline_is_expired() {
+        let config = load_or_initialize_config().unwrap();
+        let state = TimerState {
+            timer_type: TimerType::Warning,
+            stage: 0,
+            deadline: 0,
+            paused_remaining: None,
+            last_check_in: None,
+            extension: None,
+            reminder_sent: false,
+            resends: None,
+        };
+        let timer = Timer::from_state(state, &config);
+        assert_eq!(timer.get_type(), TimerType::Warning);
+        assert!(timer.expired());
+    }
+
+    #[test]
+    fn escalations() {
+        let mut config = Config {
+            timer_warning: 0,
+            timer_dead_man: 3600,
+            ..Config::default()
+        };
+        let escalation = |timer| crate::config::Escalation {
+            timer,
+            subject: "Urgent".to_string(),
+            message: "Check in now".to_string(),
+            to: None,
+        };
+        config.escalations = vec![escalation(0), escalation(0)];
+        let mut timer = Timer::new(TimerType::Warning, Duration::ZERO);
+
+        let sent: Vec<_> = (0..10).filter_map(|_| timer.tick(&config)).collect();
+        assert_eq!(
+            sent,
+            vec![Email::Warning, Email::Escalation(0), Email::Escalation(1)]
+        );
+        assert_eq!(timer.get_type(), TimerType::DeadMan);
+        assert_eq!(timer.duration, Duration::from_secs(3600));
+
+        // A check-in returns to the first stage
+        config.escalations[0].timer = 60;
+        timer = Timer::new(TimerType::Warning, Duration::ZERO);
+        assert_eq!(timer.tick(&config), Some(Email::Warning));
+        assert_eq!(timer.get_type(), TimerType::Escalation);
+        assert_eq!(timer.stage(), 0);
+        timer.reset(&config);
+        assert_eq!(timer.get_type(), TimerType::Warning);
+    }
+
+    #[test]
+    fn escalation_state_round_trips() {
+        let config = Config {
+            escalations: vec![crate::config::Escalation {
+                timer: 60,
+                subject: "Urgent".to_string(),
+                message: "Check in now".to_string(),
+                to: None,
+            }],
+            ..Config::default()
+        };
+        let state = TimerState {
+            timer_type: TimerType::Escalation,
+            stage: 0,
+            deadline: 0,
+            paused_remaining: None,
+            last_check_in: None,
+            extension: None,
+            reminder_sent: false,
+            resends: None,
+        };
+        let timer = Timer::from_state(state.clone(), &config);
+        assert_eq!(timer.get_type(), TimerType::Escalation);
+        assert_eq!(timer.duration, Duration::from_secs(60));
+
+        // The stage was removed from the config
+        let timer = Timer::from_state(state, &Config::default());
+        assert_eq!(timer.get_type(), TimerType::DeadMan);
+    }
+
+    #[test]
+    fn pause_and_resume() {
+        let mut timer =
+            Timer::with_start(TimerType::Warning, Duration::from_secs(60), secs_ago(50));
+        timer.pause();
+        assert!(timer.is_paused());
+        // Just under 10 seconds are left, which truncates to 9
+        assert_eq!(timer.remaining_seconds(), 9);
+        assert_eq!(
+            timer.label(Language::English),
+            "PAUSED (less than a minute)"
+        );
+
+        // The countdown is frozen however long the pause lasts
+        timer.start -= Duration::from_secs(3600);
+        timer.start_wall -= Duration::from_secs(3600);
+        assert!(!timer.expired());
+        assert_eq!(timer.remaining_percent(), 16);
+        timer.update(timer.elapsed(), 3600);
+        assert_eq!(timer.get_type(), TimerType::Warning);
+
+        timer.resume();
+        assert!(!timer.is_paused());
+        assert_eq!(timer.remaining_seconds(), 9);
+        assert!(!timer.expired());
+    }
+
+    #[test]
+    fn extend_postpones_the_deadline() {
+        let config = Config {
+            timer_warning: 60,
+            ..Config::default()
+        };
+        let mut timer =
+            Timer::with_start(TimerType::Warning, Duration::from_secs(60), secs_ago(50));
+        timer.extend(Duration::from_secs(100));
+        // Just under 110 seconds are left, which truncates to 109
+        assert_eq!(timer.remaining_seconds(), 109);
+        assert!(timer.remaining_percent() <= 100);
+
+        // The extension survives a restart
+        let resumed = Timer::from_state(timer.state(), &config);
+        assert!((108..=109).contains(&resumed.remaining_seconds()));
+
+        // A check-in starts a full warning period, without the extension
+        timer.check_in(&config, CheckInSource::Tui);
+        assert_eq!(timer.duration, Duration::from_secs(60));
+        assert_eq!(timer.state().extension, None);
+    }
+
+    #[test]
+    fn extend_an_expired_timer_from_now() {
+        let mut timer =
+            Timer::with_start(TimerType::Warning, Duration::from_secs(60), secs_ago(70));
+        assert!(timer.expired());
+        timer.extend(Duration::from_secs(30));
+        assert!(!timer.expired());
+        assert_eq!(timer.remaining_seconds(), 29);
+        assert_eq!(timer.state().extension, Some(40));
+    }
+
+    #[test]
+    fn huge_extensions_dont_overflow() {
+        let config = Config::default();
+        assert_eq!(extension(0), None);
+        assert_eq!(extension(u64::MAX), None);
+        assert_eq!(extension(MAX_EXTENSION.as_secs()), Some(MAX_EXTENSION));
+
+        let mut timer = Timer::new(TimerType::Warning, Duration::from_secs(60));
+        timer.extend(Duration::MAX);
+        timer.extend(Duration::MAX);
+        assert!(timer.remaining() <= MAX_EXTENSION * 2 + Duration::from_secs(60));
+        let _ = timer.snapshot(&config);
+
+        // Nor do absurd states
+        let state = TimerState {
+            deadline: u64::MAX,
+            last_check_in: Some(u64::MAX),
+            extension: Some(u64::MAX),
+            ..timer.state()
+        };
+        let resumed = Timer::from_state(state, &config);
+        let _ = resumed.snapshot(&config);
+        let _ = resumed.state();
+        assert_eq!(resumed.last_check_in(), None);
+    }
+
+    #[test]
+    fn state_deadline_is_stable() {
+        let timer = Timer::new(TimerType::Warning, Duration::from_secs(60));
+        let deadline = timer.state().deadline;
+        // Long enough for the clock to cross a second
+        for _ in 0..12 {
+            std::thread::sleep(Duration::from_millis(100));
+            assert_eq!(timer.state().deadline, deadline);
+        }
+    }
+
+    #[test]
+    fn disabled_timer_cannot_be_paused() {
+        let mut timer = Timer::new(TimerType::Disabled, Duration::ZERO);
+        timer.pause();
+        assert!(!timer.is_paused());
+    }
+
+    #[test]
+    fn paused_state_round_trips() {
+        let config = load_or_initialize_config().unwrap();
+        let mut timer = Timer::with_start(
+            TimerType::Warning,
+            Duration::from_secs(config.timer_warning),
+            secs_ago(60),
+        );
+        timer.pause();
+        let state = timer.state();
+        assert_eq!(state.paused_remaining, Some(timer.remaining_seconds()));
+
+        let loaded = Timer::from_state(state.clone(), &config);
+        assert!(loaded.is_paused());
+        assert_eq!(loaded.remaining_seconds(), timer.remaining_seconds());
+        assert_eq!(loaded.state(), state);
+    }
 }
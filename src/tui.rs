@@ -1,8 +1,16 @@
 //! TUI implementation for the Dead Man's Switch.
 
-use std::io;
-use std::time::Duration;
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use chrono::{DateTime, Local};
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
     execute,
@@ -10,18 +18,28 @@ use crossterm::{
 };
 use ratatui::{
     backend::{Backend, CrosstermBackend},
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Span, Spans},
-    widgets::{Block, Borders, Gauge, Paragraph, Wrap},
+    widgets::{Block, Borders, Clear, Gauge, Paragraph, Wrap},
     Frame, Terminal,
 };
 use thiserror::Error;
 
 use crate::{
-    config::{config_path, load_or_initialize_config, ConfigError, Email},
+    api::{self, ApiRequest},
+    config::{
+        config_path, hook_log_path, load_or_initialize_config, log_dry_run_skipped, log_wipe,
+        parse_duration, profile, save_enabled, state_path, wipe_profile, Config, ConfigError,
+        Email, Keybindings,
+    },
     email::EmailError,
-    timer::{Timer, TimerType},
+    heartbeat, hook,
+    i18n::Messages,
+    metrics::{self, Metrics},
+    notify::{email_error, send_all, ChannelError, NotificationChannel, SwitchEvent},
+    signal,
+    timer::{extension, CheckInSource, Timer, TimerType},
 };
 
 /// The ASCII art for the TUI's main block.
@@ -33,11 +51,202 @@ const ASCII_ART: [&str; 5] = [
     "██████  ███████ ██   ██ ██████      ██      ██ ██   ██ ██   ████ ███████     ███████  ███ ███  ██    ██     ██████ ██   ██",
 ];
 
+/// The outcome of the last action, shown below the timer,
+/// or a question shown as a popup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Status {
+    /// Asking whether to quit, since the switch stops running.
+    ConfirmQuit,
+    /// The test email is being sent.
+    SendingTestEmail,
+    /// The test email was sent to the address.
+    TestEmailSent(String),
+    /// The test email failed with the error.
+    TestEmailFailed(String),
+    /// Asking for the duration to extend the deadline by, typed so far.
+    Extend(String),
+    /// The deadline was extended by the duration.
+    Extended(String),
+    /// The duration typed in to extend the deadline by is invalid.
+    InvalidExtension(String),
+}
+
+/// The keys of the TUI actions, resolved from the [`Keybindings`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Keys {
+    check_in: KeyCode,
+    quit: KeyCode,
+    pause: KeyCode,
+    toggle: KeyCode,
+    test_email: KeyCode,
+    extend: KeyCode,
+}
+
+/// Keys the TUI always handles itself: `Esc` quits, `y` confirms quitting.
+const RESERVED_KEYS: [KeyCode; 3] = [KeyCode::Esc, KeyCode::Char('y'), KeyCode::Char('Y')];
+
+impl Keys {
+    /// Resolve the keybindings, falling back to the default of an invalid
+    /// one and adding why to the warnings.
+    ///
+    /// If two actions share a key, or one uses a [`RESERVED_KEYS`] key,
+    /// all the keys fall back to the defaults, with a warning.
+    fn new(keybindings: &Keybindings, warnings: &mut Vec<String>) -> Self {
+        let defaults = Keybindings::default();
+        let mut key = |action: &str, key: &str, default: &str| {
+            parse_key(key).unwrap_or_else(|| {
+                warnings.push(format!(
+                    "The `{action}` key {key:?} is invalid, using {default:?} instead."
+                ));
+                parse_key(default).expect("the default keys are valid")
+            })
+        };
+        let keys = Keys {
+            check_in: key("check_in", &keybindings.check_in, &defaults.check_in),
+            quit: key("quit", &keybindings.quit, &defaults.quit),
+            pause: key("pause", &keybindings.pause, &defaults.pause),
+            toggle: key("toggle", &keybindings.toggle, &defaults.toggle),
+            test_email: key("test_email", &keybindings.test_email, &defaults.test_email),
+            extend: key("extend", &keybindings.extend, &defaults.extend),
+        };
+
+        match keys.clash() {
+            Some(clash) => {
+                warnings.push(format!("{clash}, using the default keys instead."));
+                Keys::new(&defaults, warnings)
+            }
+            None => keys,
+        }
+    }
+
+    /// Why the keys can't be used together, if they can't.
+    fn clash(&self) -> Option<String> {
+        let actions = [
+            ("check_in", self.check_in),
+            ("quit", self.quit),
+            ("pause", self.pause),
+            ("toggle", self.toggle),
+            ("test_email", self.test_email),
+            ("extend", self.extend),
+        ];
+        for (i, (action, key)) in actions.iter().enumerate() {
+            if RESERVED_KEYS.contains(key) {
+                return Some(format!(
+                    "The `{action}` key {:?} is reserved",
+                    key_name(*key)
+                ));
+            }
+            if let Some((other, _)) = actions[..i].iter().find(|(_, other)| other == key) {
+                return Some(format!(
+                    "The `{other}` and `{action}` keys are both {:?}",
+                    key_name(*key)
+                ));
+            }
+        }
+        None
+    }
+}
+
+/// Parse a key of the [`Keybindings`]: a single character,
+/// or `Enter`, `Esc`, `Space`, `Tab` or `Backspace`.
+fn parse_key(key: &str) -> Option<KeyCode> {
+    let mut chars = key.chars();
+    match (chars.next(), chars.next()) {
+        (Some(char), None) => Some(KeyCode::Char(char)),
+        _ => match key.to_lowercase().as_str() {
+            "enter" => Some(KeyCode::Enter),
+            "esc" => Some(KeyCode::Esc),
+            "space" => Some(KeyCode::Char(' ')),
+            "tab" => Some(KeyCode::Tab),
+            "backspace" => Some(KeyCode::Backspace),
+            _ => None,
+        },
+    }
+}
+
+/// The name of a key in the legend, the inverse of [`parse_key`].
+fn key_name(key: KeyCode) -> String {
+    match key {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(char) => char.to_string(),
+        KeyCode::Enter => "Enter".to_string(),
+        KeyCode::Esc => "Esc".to_string(),
+        KeyCode::Tab => "Tab".to_string(),
+        KeyCode::Backspace => "Backspace".to_string(),
+        key => format!("{key:?}"),
+    }
+}
+
+/// Number of notifications kept in the [`Activity`] log.
+const LOG_LEN: usize = 5;
+
+/// What happened recently, shown below the timer.
+#[derive(Debug, Default)]
+struct Activity {
+    /// The outcome of the last action, or a question.
+    status: Option<Status>,
+    /// The outcomes of the last [`LOG_LEN`] notifications, oldest first.
+    log: VecDeque<Spans<'static>>,
+}
+
+impl Activity {
+    /// Log the outcome of a notification, given the errors of the channels.
+    fn log<E: Display>(&mut self, name: &str, errors: &[E], messages: &Messages) {
+        let time = Local::now().format("%H:%M:%S");
+        let mut push = |entry: Spans<'static>| {
+            if self.log.len() == LOG_LEN {
+                self.log.pop_front();
+            }
+            self.log.push_back(entry);
+        };
+        if errors.is_empty() {
+            push(Spans::from(Span::styled(
+                format!("{time} {name}: {}", messages.sent),
+                Style::default().fg(Color::Green),
+            )));
+        }
+        for error in errors {
+            push(Spans::from(Span::styled(
+                format!("{time} {name}: {}{error}", messages.failed),
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            )));
+        }
+    }
+}
+
 /// The main UI function.
 ///
 /// This function will render the UI.
-/// It's a simple UI with 3 blocks.
-fn ui<B: Backend>(f: &mut Frame<B>, config_path: &str, timer: &Timer) {
+/// It's a simple UI with 4 blocks, plus the status, the log of the
+/// notifications and the config warnings if any.
+/// The quit confirmation is a popup over the rest.
+fn ui<B: Backend>(
+    f: &mut Frame<B>,
+    config_path: &str,
+    timer: &Timer,
+    config: &Config,
+    keys: &Keys,
+    activity: &Activity,
+    warnings: &[String],
+) {
+    let enabled = config.enabled;
+    let messages = config.language.messages();
+    let snapshot = timer.snapshot(config);
+    let status_height = match activity.status {
+        None | Some(Status::ConfirmQuit | Status::Extend(_)) => 0,
+        Some(_) => 3,
+    };
+    let log_height = if activity.log.is_empty() {
+        0
+    } else {
+        activity.log.len() as u16 + 2
+    };
+    let warnings_height = if warnings.is_empty() {
+        0
+    } else {
+        warnings.len() as u16 + 2
+    };
+
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .margin(2)
@@ -45,61 +254,130 @@ fn ui<B: Backend>(f: &mut Frame<B>, config_path: &str, timer: &Timer) {
             [
                 Constraint::Max(3),
                 Constraint::Percentage(40),
-                Constraint::Max(6),
+                Constraint::Max(7),
                 Constraint::Max(5),
+                Constraint::Length(status_height),
+                Constraint::Length(log_height),
+                Constraint::Length(warnings_height),
             ]
             .as_ref(),
         )
         .split(f.size());
 
-    let legend_widget = legend_block();
+    let legend_widget = legend_block(keys, messages);
     f.render_widget(legend_widget, chunks[0]);
 
     let ascii_widget = ascii_block(ASCII_ART.as_ref());
     f.render_widget(ascii_widget, chunks[1]);
 
-    let instructions_widget = instructions_block(config_path);
+    let instructions_widget = instructions_block(
+        config_path,
+        timer.last_check_in(),
+        key_name(keys.check_in),
+        messages,
+    );
     f.render_widget(instructions_widget, chunks[2]);
 
-    let gauge_title = timer.title();
-    let gauge_style = timer.gauge_style();
-    let label_style = timer.label_style();
-    let label = timer.label();
-    let current_percent = timer.remaining_percent();
+    let (gauge_title, gauge_style, label_style) = if snapshot.timer_type == TimerType::Disabled {
+        // Neutral until armed
+        let neutral = Style::default().fg(Color::DarkGray);
+        (timer.title(messages), neutral, neutral)
+    } else if enabled {
+        let title = match snapshot.deadline {
+            Some(deadline) => format!(
+                "{} · {}{}",
+                timer.title(messages),
+                messages.fires_on,
+                DateTime::<Local>::from(UNIX_EPOCH + Duration::from_secs(deadline))
+                    .format("%Y-%m-%d %H:%M")
+            ),
+            None => timer.title(messages),
+        };
+        (title, timer.gauge_style(config), timer.label_style(config))
+    } else {
+        // Greyed out, the switch never fires while disabled
+        let greyed_out = Style::default().fg(Color::DarkGray);
+        (
+            format!("{} ({})", timer.title(messages), messages.disabled),
+            greyed_out,
+            greyed_out,
+        )
+    };
     let timer_widget = timer_block(
+        messages.timer,
         gauge_title,
-        current_percent,
-        label,
+        snapshot.remaining_percent,
+        snapshot.label,
+        timer.title_style(config),
         gauge_style,
         label_style,
     );
     f.render_widget(timer_widget, chunks[3]);
+
+    if !activity.log.is_empty() {
+        let log_widget = log_block(&activity.log, messages);
+        f.render_widget(log_widget, chunks[5]);
+    }
+
+    if !warnings.is_empty() {
+        let warnings_widget = warnings_block(warnings, messages);
+        f.render_widget(warnings_widget, chunks[6]);
+    }
+
+    match &activity.status {
+        Some(question @ (Status::ConfirmQuit | Status::Extend(_))) => {
+            let popup = centered_rect(60, 3, f.size());
+            f.render_widget(Clear, popup);
+            f.render_widget(status_block(question, messages), popup);
+        }
+        Some(status) => f.render_widget(status_block(status, messages), chunks[4]),
+        None => {}
+    }
+}
+
+/// A rectangle `height` lines high and `percent_x` percent wide,
+/// centered in `area`.
+fn centered_rect(percent_x: u16, height: u16, area: Rect) -> Rect {
+    let width = area.width * percent_x / 100;
+    let height = height.min(area.height);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
 }
 
 /// The legend block.
 ///
 /// Contains the keys legend for the TUI.
-fn legend_block() -> Paragraph<'static> {
-    let text = vec![Spans::from(vec![
-        Span::styled(
-            "c",
-            Style::default()
-                .fg(Color::Green)
-                .add_modifier(Modifier::BOLD),
-        ),
-        Span::raw(":Check-In"),
-        Span::raw("    "),
-        Span::styled(
-            "q/Esc",
+fn legend_block(keys: &Keys, messages: &Messages) -> Paragraph<'static> {
+    let quit = format!("{}/Esc", key_name(keys.quit));
+    let legend = [
+        (key_name(keys.check_in), messages.check_in),
+        (key_name(keys.toggle), messages.toggle),
+        (key_name(keys.pause), messages.pause),
+        (key_name(keys.test_email), messages.test_email),
+        (key_name(keys.extend), messages.extend),
+        (quit, messages.quit),
+    ];
+    let mut spans = vec![];
+    for (key, action) in legend {
+        if !spans.is_empty() {
+            spans.push(Span::raw("    "));
+        }
+        spans.push(Span::styled(
+            key,
             Style::default()
                 .fg(Color::Green)
                 .add_modifier(Modifier::BOLD),
-        ),
-        Span::raw(":Quit"),
-    ])];
+        ));
+        spans.push(Span::raw(format!(":{action}")));
+    }
+    let text = vec![Spans::from(spans)];
     let block = Paragraph::new(text)
         .alignment(ratatui::layout::Alignment::Center)
-        .block(Block::default().title("Keys").borders(Borders::ALL))
+        .block(Block::default().title(messages.keys).borders(Borders::ALL))
         .wrap(Wrap { trim: true });
     block
 }
@@ -107,7 +385,18 @@ fn legend_block() -> Paragraph<'static> {
 /// The Instructions block.
 ///
 /// Contains the instructions for the TUI.
-fn instructions_block(config_path: &str) -> Paragraph<'static> {
+fn instructions_block(
+    config_path: &str,
+    last_check_in: Option<SystemTime>,
+    check_in_key: String,
+    messages: &Messages,
+) -> Paragraph<'static> {
+    let last_check_in = match last_check_in {
+        Some(last_check_in) => DateTime::<Local>::from(last_check_in)
+            .format("%Y-%m-%d %H:%M")
+            .to_string(),
+        None => messages.never.to_string(),
+    };
     let text = vec![
         Spans::from(vec![
             Span::styled(
@@ -116,14 +405,14 @@ fn instructions_block(config_path: &str) -> Paragraph<'static> {
                     .fg(Color::Cyan)
                     .add_modifier(Modifier::BOLD),
             ),
-            Span::raw("Edit the Config at "),
+            Span::raw(messages.edit_config),
             Span::styled(
                 config_path.to_string(),
                 Style::default()
                     .fg(Color::Green)
                     .add_modifier(Modifier::BOLD),
             ),
-            Span::raw(" and modify the settings."),
+            Span::raw(messages.modify_settings),
         ]),
         Spans::from(vec![
             Span::styled(
@@ -132,18 +421,14 @@ fn instructions_block(config_path: &str) -> Paragraph<'static> {
                     .fg(Color::Cyan)
                     .add_modifier(Modifier::BOLD),
             ),
-            Span::raw(
-                "Check-In with ",
-            ),
+            Span::raw(messages.check_in_with),
             Span::styled(
-                "c",
+                check_in_key,
                 Style::default()
                     .fg(Color::Green)
                     .add_modifier(Modifier::BOLD),
             ),
-            Span::raw(
-                " within the warning time.",
-            ),
+            Span::raw(messages.within_warning_time),
         ]),
         Spans::from(vec![
             Span::styled(
@@ -152,14 +437,107 @@ fn instructions_block(config_path: &str) -> Paragraph<'static> {
                     .fg(Color::Cyan)
                     .add_modifier(Modifier::BOLD),
             ),
-            Span::raw(
-                "Otherwise the Dead Man's Switch will be triggered and the message with optional attachment will be sent.",
+            Span::raw(messages.otherwise),
+        ]),
+        Spans::from(vec![
+            Span::raw(messages.last_check_in),
+            Span::styled(
+                last_check_in,
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
             ),
         ]),
     ];
     let block = Paragraph::new(text)
         .alignment(ratatui::layout::Alignment::Left)
-        .block(Block::default().title("Instructions").borders(Borders::ALL))
+        .block(
+            Block::default()
+                .title(messages.instructions)
+                .borders(Borders::ALL),
+        )
+        .wrap(Wrap { trim: true });
+    block
+}
+
+/// The status block.
+///
+/// Contains the outcome of the last action, e.g. sending the test email.
+fn status_block(status: &Status, messages: &Messages) -> Paragraph<'static> {
+    let text = match status {
+        Status::ConfirmQuit => Span::styled(
+            messages.confirm_quit,
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Status::SendingTestEmail => Span::raw(messages.test_email_sending),
+        Status::TestEmailSent(to) => Span::styled(
+            format!("{}{to}", messages.test_email_sent),
+            Style::default().fg(Color::Green),
+        ),
+        Status::TestEmailFailed(error) => Span::styled(
+            format!("{}{error}", messages.test_email_failed),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ),
+        Status::Extend(input) => Span::styled(
+            format!("{}{input}", messages.extend_by),
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Status::Extended(duration) => Span::styled(
+            format!("{}{duration}", messages.extended),
+            Style::default().fg(Color::Green),
+        ),
+        Status::InvalidExtension(input) => Span::styled(
+            format!("{}{input:?}", messages.invalid_duration),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ),
+    };
+
+    let block = Paragraph::new(Spans::from(text))
+        .alignment(ratatui::layout::Alignment::Left)
+        .block(Block::default().borders(Borders::ALL))
+        .wrap(Wrap { trim: true });
+    block
+}
+
+/// The log block.
+///
+/// Contains the outcomes of the last notifications, see [`Activity`].
+fn log_block(log: &VecDeque<Spans<'static>>, messages: &Messages) -> Paragraph<'static> {
+    let text: Vec<Spans<'static>> = log.iter().cloned().collect();
+    let block = Paragraph::new(text)
+        .alignment(ratatui::layout::Alignment::Left)
+        .block(Block::default().title(messages.log).borders(Borders::ALL))
+        .wrap(Wrap { trim: true });
+    block
+}
+
+/// The warnings block.
+///
+/// Contains the non-fatal config warnings, see [`Config::warnings`].
+///
+/// [`Config::warnings`]: crate::config::Config::warnings
+fn warnings_block(warnings: &[String], messages: &Messages) -> Paragraph<'static> {
+    let text: Vec<Spans<'_>> = warnings
+        .iter()
+        .map(|warning| {
+            Spans::from(Span::styled(
+                warning.clone(),
+                Style::default().fg(Color::Yellow),
+            ))
+        })
+        .collect();
+
+    let block = Paragraph::new(text)
+        .alignment(ratatui::layout::Alignment::Left)
+        .block(
+            Block::default()
+                .title(messages.warnings)
+                .borders(Borders::ALL),
+        )
         .wrap(Wrap { trim: true });
     block
 }
@@ -194,30 +572,28 @@ fn ascii_block(content: &[&'static str]) -> Paragraph<'static> {
 ///
 /// ## Parameters
 ///
+/// - `timer`: The localized word for "Timer".
 /// - `title`: The title for the timer.
 /// - `current_percent`: The current percentage of the timer.
 /// - `label`: The label for the timer.
+/// - `title_style`: The style of the title.
 /// - `gauge_style`: The [`GaugeStyle`] for the timer.
 ///
 /// ## Notes
 ///
-/// The timer will be green if is still counting the warning time.
-/// Eventually, it will turn red when the warning time is done,
-/// and start counting the dead man's switch timer.
+/// The timer will be green while plenty of time remains.
+/// It turns yellow at the `caution_threshold_percent`, and red and bold
+/// at the `warning_threshold_percent` of the config.
 fn timer_block(
+    timer: &str,
     title: String,
     current_percent: u16,
     label: String,
+    title_style: Style,
     gauge_style: Style,
     label_style: Style,
 ) -> Gauge<'static> {
-    let title = Span::styled(
-        format!("Timer: {title}"),
-        match current_percent {
-            0..=30 => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
-            _ => Style::default().fg(Color::Green),
-        },
-    );
+    let title = Span::styled(format!("{timer}: {title}"), title_style);
     Gauge::default()
         .percent(current_percent)
         .gauge_style(gauge_style)
@@ -225,35 +601,54 @@ fn timer_block(
         .block(Block::default().title(title).borders(Borders::ALL))
 }
 
+/// How close the timer is to firing, by the thresholds of the config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Urgency {
+    /// Plenty of time remains.
+    Calm,
+    /// At or below the `caution_threshold_percent`.
+    Caution,
+    /// At or below the `warning_threshold_percent`.
+    Urgent,
+}
+
 impl Timer {
-    /// Determine the gauge style based on the timer state
-    fn gauge_style(&self) -> Style {
+    /// Determine the urgency based on the remaining percentage
+    fn urgency(&self, config: &Config) -> Urgency {
         let percent = self.remaining_percent();
-        let style = if percent > 30 {
-            Style::default().fg(Color::Green)
+        if percent <= config.warning_threshold_percent {
+            Urgency::Urgent
+        } else if percent <= config.caution_threshold_percent {
+            Urgency::Caution
         } else {
-            Style::default().fg(Color::Red)
-        };
-        Style::default().fg(style.fg.unwrap())
+            Urgency::Calm
+        }
     }
 
-    // Determine the label style based on the timer state
-    fn label_style(&self) -> Style {
-        let percent = self.remaining_percent();
+    /// Determine the title style based on the timer state
+    fn title_style(&self, config: &Config) -> Style {
+        match self.urgency(config) {
+            Urgency::Calm => Style::default().fg(Color::Green),
+            Urgency::Caution => Style::default().fg(Color::Yellow),
+            Urgency::Urgent => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        }
+    }
 
-        let style = if percent > 30 {
-            Style::default().fg(Color::DarkGray)
-        } else {
-            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
-        };
-        Style::default().fg(style.fg.unwrap())
+    /// Determine the gauge style based on the timer state
+    fn gauge_style(&self, config: &Config) -> Style {
+        match self.urgency(config) {
+            Urgency::Calm => Style::default().fg(Color::Green),
+            Urgency::Caution => Style::default().fg(Color::Yellow),
+            Urgency::Urgent => Style::default().fg(Color::Red),
+        }
     }
 
-    // Determine the Widget title based on the type of Timer
-    fn title(&self) -> String {
-        match self.get_type() {
-            TimerType::Warning => "Warning".to_string(),
-            TimerType::DeadMan => "Dead Man's Switch".to_string(),
+    // Determine the label style based on the timer state
+    fn label_style(&self, config: &Config) -> Style {
+        match self.urgency(config) {
+            Urgency::Calm => Style::default().fg(Color::DarkGray),
+            Urgency::Caution => Style::default().fg(Color::Yellow),
+            Urgency::Urgent => Style::default().fg(Color::Red),
         }
     }
 }
@@ -271,13 +666,167 @@ pub enum TuiError {
     /// [`EmailError`] blanket error conversion.
     #[error(transparent)]
     EmailError(#[from] EmailError),
+    /// [`ChannelError`] blanket error conversion.
+    #[error(transparent)]
+    ChannelError(#[from] ChannelError),
+}
+
+/// Send the event on all channels, counting it in the [`Metrics`] and
+/// logging it in the [`Activity`].
+///
+/// All channels are tried, and only the email failing is an error:
+/// the other channels failing is only logged, see [`email_error`].
+fn notify(
+    channels: &[Box<dyn NotificationChannel>],
+    event: &SwitchEvent,
+    metrics: &Mutex<Metrics>,
+    activity: &mut Activity,
+    messages: &Messages,
+) -> Result<(), TuiError> {
+    let errors = send_all(channels, event);
+    if let Ok(mut metrics) = metrics.lock() {
+        metrics.record_notification(&errors);
+    }
+    activity.log(&event.subject, &errors, messages);
+    match email_error(errors) {
+        Some(error) => Err(error.into()),
+        None => Ok(()),
+    }
+}
+
+/// Send the `email` in the background, so a slow server doesn't hold up
+/// the UI, reporting the result to `results`.
+///
+/// Used for the check-in confirmation and the test email.
+fn spawn_email(config: &Config, email: Email, results: Sender<Result<(), EmailError>>) {
+    let config = config.clone();
+    let _ = thread::Builder::new()
+        .name("email".to_string())
+        .spawn(move || {
+            // The TUI may have quit in the meantime
+            let _ = results.send(config.send_email(email));
+        });
+}
+
+/// When the file at `path` was last modified, if it exists.
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Alert the user that the warning was sent, see [`Config::alert_on_warning`].
+///
+/// Rings the terminal bell, and with the `desktop-notifications` feature
+/// shows a desktop notification, if a notification server is running.
+#[cfg_attr(not(feature = "desktop-notifications"), allow(unused_variables))]
+fn alert<W: Write>(terminal: &mut W, messages: &Messages) -> io::Result<()> {
+    terminal.write_all(b"\x07")?;
+    terminal.flush()?;
+    #[cfg(feature = "desktop-notifications")]
+    {
+        // Best effort, the bell already rang
+        let _ = notify_rust::Notification::new()
+            .summary("Dead Man's Switch")
+            .body(messages.warning_alert)
+            .show();
+    }
+    Ok(())
+}
+
+/// Keep going if the config directory is read-only, adding why to the
+/// warnings.
+///
+/// Returns whether the write succeeded.
+fn tolerate_read_only(
+    result: Result<(), ConfigError>,
+    warnings: &mut Vec<String>,
+) -> Result<bool, TuiError> {
+    match result {
+        Ok(()) => Ok(true),
+        Err(error @ ConfigError::ReadOnly(_)) => {
+            let warning = format!("Changes won't be saved: {error}.");
+            if !warnings.contains(&warning) {
+                warnings.push(warning);
+            }
+            Ok(false)
+        }
+        Err(error) => Err(error.into()),
+    }
 }
 
 /// Run the TUI.
 ///
-/// This function will setup the terminal, run the main loop, and then
-/// restore the terminal.
+/// This function will load the config and the timer, setup the terminal,
+/// run the main loop, and then restore the terminal.
+/// An invalid config is reported before the terminal is touched.
 pub fn run() -> Result<(), TuiError> {
+    // Instantiate the Config
+    let mut config = load_or_initialize_config()?;
+
+    // Prove to a third party that the switch is alive
+    let _heartbeat = heartbeat::spawn(&config);
+
+    // Non-fatal config problems shown in the UI
+    let mut warnings = config.warnings();
+
+    // The keys of the actions, invalid ones are warned about
+    let keys = Keys::new(&config.keybindings, &mut warnings);
+
+    // Find out about wrong SMTP settings now, not when the switch fires
+    if let Err(error) = config.check_smtp_connection() {
+        warnings.push(format!("SMTP connection failed: {error}"));
+    }
+
+    // Check in with Signal messages, if configured
+    let signal_check_ins = signal::spawn_check_ins(&config);
+    if let Some(signal) = &config.signal {
+        if let Err(error) = signal.about() {
+            warnings.push(format!("signal-cli is unavailable: {error}"));
+        }
+    }
+
+    // Check in with the HTTP API, if configured
+    let api_requests = api::spawn_check_ins(&config).unwrap_or_else(|error| {
+        warnings.push(format!(
+            "The check-in API can't listen on {}: {error}",
+            config.api_listen
+        ));
+        None
+    });
+
+    // Serve the metrics, if configured
+    let metrics = Arc::new(Mutex::new(Metrics::default()));
+    if let Err(error) = metrics::spawn(&config, Arc::clone(&metrics)) {
+        warnings.push(format!("The metrics can't be served: {error}"));
+    }
+
+    // Notification channels
+    let channels = config.channels();
+
+    // Check-in confirmations sent in the background, see `ack_email_on_checkin`
+    let (ack_sender, acks) = mpsc::channel();
+
+    // The test email is sent in the background too
+    let (test_email_sender, test_emails) = mpsc::channel::<Result<(), EmailError>>();
+
+    // Get config OS-agnostic path
+    let config_file = config_path()?;
+    let config_path = config_file.to_string_lossy().to_string();
+
+    // Resume the persisted Timer or rest until the first check-in arms it
+    let state_path = state_path()?;
+    let mut timer = match Timer::load(&state_path, &config)? {
+        Some(timer) => timer,
+        None => Timer::unarmed(config.clock),
+    };
+    let mut saved_state = None;
+    let mut state_modified = modified(&state_path);
+    let mut persist = true;
+    let mut drawn = None;
+    let mut redraw = true;
+    let mut activity = Activity::default();
+
     // setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -285,49 +834,260 @@ pub fn run() -> Result<(), TuiError> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    // Instantiate the Config
-    let config = load_or_initialize_config()?;
+    // Main loop
+    loop {
+        // Whether the switch fired and is done
+        let mut done = false;
 
-    // Get config OS-agnostic path
-    let config_path = config_path()?.to_string_lossy().to_string();
+        // Send the emails that are due, a disabled switch is left alone
+        match timer.tick(&config) {
+            Some(Email::DeadMan) => {
+                // The first dead man's email fires the switch,
+                // the others are its resends with the acknowledgment link
+                let resend = timer.fired();
+                timer.fire(&config)?;
+                let config = Config {
+                    acknowledge_url: timer.acknowledge_url(&config),
+                    ..config.clone()
+                };
+                let event = config.event(Email::DeadMan);
+                let result = notify(
+                    &config.channels(),
+                    &event,
+                    &metrics,
+                    &mut activity,
+                    config.language.messages(),
+                );
+                if resend {
+                    // A failure is in the log, the next resend tries again
+                    redraw = true;
+                } else {
+                    // Only the email failing aborts, the other channels are
+                    // in the log
+                    result?;
+                    hook::log(
+                        &hook_log_path()?,
+                        &hook::run_deadman_hook(&config, SystemTime::now()),
+                    )?;
+                    // Only reached if the dead man's email was sent
+                    if config.wipe_config_on_fire && config.dry_run {
+                        log_dry_run_skipped(&format!(
+                            "not wiped: the profile {:?} of the config {}",
+                            profile()?,
+                            config_file.display()
+                        ))?;
+                    } else if config.wipe_config_on_fire {
+                        let profile = profile()?;
+                        let result = wipe_profile(&config_file, &profile);
+                        log_wipe(&hook_log_path()?, &config_file, &profile, &result)?;
+                    }
+                }
+                done = !timer.resending(&config);
+            }
+            Some(kind) => {
+                // Once per warning period, as the warning is only sent once
+                if kind == Email::Warning && config.alert_on_warning {
+                    alert(terminal.backend_mut(), config.language.messages())?;
+                }
+                // A failure is in the log, the countdown goes on
+                let event = config.event(kind);
+                let _ = notify(
+                    &channels,
+                    &event,
+                    &metrics,
+                    &mut activity,
+                    config.language.messages(),
+                );
+                redraw = true;
+            }
+            None => {}
+        }
+        let snapshot = timer.snapshot(&config);
+        if let Ok(mut metrics) = metrics.lock() {
+            metrics.update(&snapshot, config.enabled);
+        }
 
-    // Create a new Timer
-    let mut timer = Timer::new(
-        TimerType::Warning,
-        Duration::from_secs(config.timer_warning),
-    );
+        // Persist the state whenever it changes, so that even an unclean
+        // exit resumes the countdown
+        let state = timer.state();
+        if persist && saved_state.as_ref() != Some(&state) {
+            persist = tolerate_read_only(timer.save(&state_path), &mut warnings)?;
+            saved_state = Some(state);
+            state_modified = modified(&state_path);
+            redraw |= !persist;
+        }
 
-    // Main loop
-    loop {
-        let elapsed = timer.elapsed();
-        timer.update(elapsed, config.timer_dead_man);
-        terminal.draw(|f| ui(f, &config_path, &timer))?;
+        // Only redraw when the displayed timer changes or on input
+        let displayed = (
+            snapshot.timer_type,
+            snapshot.remaining_seconds,
+            snapshot.paused,
+            timer.last_check_in(),
+            config.enabled,
+        );
+        if redraw || drawn != Some(displayed) {
+            terminal.draw(|f| {
+                ui(
+                    f,
+                    &config_path,
+                    &timer,
+                    &config,
+                    &keys,
+                    &activity,
+                    &warnings,
+                )
+            })?;
+            drawn = Some(displayed);
+            redraw = false;
+        }
 
-        // Poll for events
-        if crossterm::event::poll(Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char('q') | KeyCode::Esc => break, // Quit
-                    KeyCode::Char('c') => timer.reset(&config), // Check-In
-                    _ => {}
+        // Report the test email once it's sent
+        while let Ok(result) = test_emails.try_recv() {
+            let errors: Vec<EmailError> = result.err().into_iter().collect();
+            let messages = config.language.messages();
+            activity.log(messages.test_email, &errors, messages);
+            activity.status = Some(match errors.first() {
+                None => Status::TestEmailSent(config.from.clone()),
+                Some(error) => Status::TestEmailFailed(error.to_string()),
+            });
+            redraw = true;
+        }
+
+        if let Some(check_ins) = &signal_check_ins {
+            while check_ins.try_recv().is_ok() {
+                timer.check_in(&config, CheckInSource::Signal);
+            }
+        }
+        if let Some(api_requests) = &api_requests {
+            while let Ok(api_request) = api_requests.try_recv() {
+                match api_request {
+                    ApiRequest::CheckIn => {
+                        timer.check_in(&config, CheckInSource::Api);
+                        if config.ack_email_on_checkin {
+                            spawn_email(&config, Email::CheckInConfirmation, ack_sender.clone());
+                        }
+                    }
+                    ApiRequest::Extend(extra) => timer.extend(extra),
+                    ApiRequest::Acknowledge(token) => {
+                        done |= timer.acknowledge(&token);
+                    }
                 }
             }
         }
 
-        // Condition to exit the loop
-        if timer.expired() {
-            match timer.get_type() {
-                TimerType::Warning => {
-                    config.send_email(Email::Warning)?;
+        // The switch fired, relaunching starts over
+        if done {
+            if state_path.exists() {
+                fs::remove_file(&state_path)?;
+            }
+            break;
+        }
+        while let Ok(result) = acks.try_recv() {
+            let errors: Vec<EmailError> = result.err().into_iter().collect();
+            let subject = config.subject(Email::CheckInConfirmation);
+            activity.log(&subject, &errors, config.language.messages());
+            redraw = true;
+        }
+
+        // Adopt check-ins and triggers from the command line,
+        // which saves them to the state
+        let state_modified_now = modified(&state_path);
+        if persist && state_modified_now != state_modified {
+            state_modified = state_modified_now;
+            if let Ok(Some(saved)) = Timer::load(&state_path, &config) {
+                let triggered = (saved.fired() && !timer.fired())
+                    || (saved.get_type() == TimerType::Disabled
+                        && timer.get_type() != TimerType::Disabled);
+                if saved.last_check_in() > timer.last_check_in() {
+                    timer.check_in(&config, CheckInSource::Cli);
+                } else if triggered {
+                    timer = saved;
                 }
-                TimerType::DeadMan => {
-                    config.send_email(Email::DeadMan)?;
-                    break;
+            }
+        }
+
+        // Poll for events
+        if crossterm::event::poll(Duration::from_millis(config.tui_refresh_ms))? {
+            match event::read()? {
+                Event::Key(key) => {
+                    redraw = true;
+                    if activity.status == Some(Status::ConfirmQuit) {
+                        // Only an explicit confirmation quits
+                        if matches!(key.code, KeyCode::Char('y' | 'Y')) {
+                            break;
+                        }
+                        activity.status = None;
+                    } else if let Some(Status::Extend(input)) = &mut activity.status {
+                        match key.code {
+                            KeyCode::Enter => {
+                                let input = input.trim().to_string();
+                                let extra = parse_duration(&input).and_then(extension);
+                                activity.status = Some(match extra {
+                                    Some(extra) => {
+                                        timer.extend(extra);
+                                        Status::Extended(input)
+                                    }
+                                    None => Status::InvalidExtension(input),
+                                });
+                            }
+                            KeyCode::Esc => activity.status = None,
+                            KeyCode::Backspace => {
+                                input.pop();
+                            }
+                            KeyCode::Char(char) => input.push(char),
+                            _ => {}
+                        }
+                    } else {
+                        match key.code {
+                            code if code == keys.quit || code == KeyCode::Esc => {
+                                activity.status = Some(Status::ConfirmQuit)
+                            } // Quit
+                            code if code == keys.check_in => {
+                                timer.check_in(&config, CheckInSource::Tui)
+                            } // Check-In
+                            code if code == keys.pause => {
+                                // Pause/Resume
+                                if timer.is_paused() {
+                                    timer.resume();
+                                } else {
+                                    timer.pause();
+                                }
+                            }
+                            code if code == keys.test_email
+                                && activity.status != Some(Status::SendingTestEmail) =>
+                            {
+                                // Test Email, one at a time
+                                spawn_email(&config, Email::Test, test_email_sender.clone());
+                                activity.status = Some(Status::SendingTestEmail);
+                            }
+                            code if code == keys.extend => {
+                                activity.status = Some(Status::Extend(String::new()))
+                            } // Extend
+                            code if code == keys.toggle => {
+                                // Enable/Disable, restarting the countdown when enabled
+                                config.enabled = !config.enabled;
+                                if config.enabled {
+                                    timer.reset(&config);
+                                }
+                                // Only `enabled`, not the `DMS_*` overrides
+                                let saved = save_enabled(&config_file, &profile()?, config.enabled);
+                                tolerate_read_only(saved, &mut warnings)?;
+                            }
+                            _ => {}
+                        }
+                    }
                 }
+                Event::Resize(_, _) => redraw = true,
+                _ => {}
             }
         }
     }
 
+    // Persist the state so relaunching resumes the countdown
+    if persist && state_path.exists() {
+        timer.save(&state_path)?;
+    }
+
     // Restore terminal
     disable_raw_mode()?;
     execute!(
@@ -339,3 +1099,61 @@ pub fn run() -> Result<(), TuiError> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys() {
+        let mut warnings = vec![];
+        let keys = Keys::new(
+            &Keybindings {
+                check_in: "Enter".to_string(),
+                quit: "nope".to_string(),
+                ..Keybindings::default()
+            },
+            &mut warnings,
+        );
+        assert_eq!(keys.check_in, KeyCode::Enter);
+        assert_eq!(keys.quit, KeyCode::Char('q'));
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("`quit`"));
+    }
+
+    #[test]
+    fn test_keys_clash() {
+        let defaults = Keys::new(&Keybindings::default(), &mut vec![]);
+
+        for (keybindings, warning) in [
+            (
+                Keybindings {
+                    pause: "c".to_string(),
+                    ..Keybindings::default()
+                },
+                "The `check_in` and `pause` keys are both \"c\"",
+            ),
+            (
+                Keybindings {
+                    quit: "y".to_string(),
+                    ..Keybindings::default()
+                },
+                "The `quit` key \"y\" is reserved",
+            ),
+            (
+                Keybindings {
+                    extend: "Esc".to_string(),
+                    ..Keybindings::default()
+                },
+                "The `extend` key \"Esc\" is reserved",
+            ),
+        ] {
+            let mut warnings = vec![];
+            assert_eq!(Keys::new(&keybindings, &mut warnings), defaults);
+            assert_eq!(
+                warnings,
+                vec![format!("{warning}, using the default keys instead.")]
+            );
+        }
+    }
+}
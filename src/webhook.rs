@@ -0,0 +1,113 @@
+//! Webhook notifications of the Dead Man's Switch.
+//!
+//! If `webhook_url` is set, the switch firing is also POSTed there as JSON,
+//! e.g. to push it to a custom service or IFTTT, see [`WebhookChannel`].
+
+use std::time::UNIX_EPOCH;
+
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::config::Email;
+use crate::notify::{ChannelError, NotificationChannel, SwitchEvent};
+
+/// Webhook errors.
+#[derive(Error, Debug)]
+pub enum WebhookError {
+    /// Error when calling the webhook.
+    #[error(transparent)]
+    HttpError(#[from] Box<ureq::Error>),
+}
+
+/// The JSON payload POSTed to the webhook.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct WebhookPayload<'a> {
+    /// The event, `dead_man`.
+    pub event: &'a str,
+    /// When the switch fired, in seconds since the Unix epoch.
+    pub timestamp: u64,
+    /// The subject of the dead man's email.
+    pub subject: &'a str,
+    /// The message of the dead man's email.
+    pub message: &'a str,
+}
+
+/// The webhook channel.
+///
+/// POSTs a [`WebhookPayload`] to the URL when the switch fires,
+/// other events are ignored.
+#[derive(Debug, Clone)]
+pub struct WebhookChannel {
+    /// The URL to POST to.
+    url: String,
+}
+
+impl WebhookChannel {
+    /// Create a new webhook channel.
+    pub fn new(url: String) -> Self {
+        WebhookChannel { url }
+    }
+}
+
+impl NotificationChannel for WebhookChannel {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    fn send(&self, event: &SwitchEvent) -> Result<(), ChannelError> {
+        if event.kind != Email::DeadMan {
+            return Ok(());
+        }
+        let payload = WebhookPayload {
+            event: "dead_man",
+            timestamp: event
+                .timestamp
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            subject: &event.subject,
+            message: &event.message,
+        };
+        ureq::post(&self.url)
+            .send_json(payload)
+            .map_err(|error| WebhookError::from(Box::new(error)))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::test_util::serve_once;
+
+    #[test]
+    fn test_webhook_channel_send() {
+        let (url, server) = serve_once("{}");
+        let channel = WebhookChannel::new(format!("{url}/hook"));
+        let event = Config::default().event(Email::DeadMan);
+
+        channel.send(&event).unwrap();
+        let (request_line, body) = server.join().unwrap();
+        assert!(request_line.starts_with("POST /hook "));
+        let body: ureq::serde_json::Value = ureq::serde_json::from_str(&body).unwrap();
+        assert_eq!(body["event"], "dead_man");
+        assert_eq!(body["subject"], event.subject.as_str());
+        assert_eq!(body["message"], event.message.as_str());
+        assert!(body["timestamp"].as_u64().unwrap() > 0);
+    }
+
+    #[test]
+    fn test_webhook_only_when_fired() {
+        // Nothing listens on the discard port, so sending would fail
+        let channel = WebhookChannel::new("http://127.0.0.1:9".to_string());
+        let event = Config::default().event(Email::Warning);
+        assert!(channel.send(&event).is_ok());
+
+        let event = Config::default().event(Email::DeadMan);
+        assert!(matches!(
+            channel.send(&event),
+            Err(ChannelError::WebhookError(_))
+        ));
+    }
+}